@@ -0,0 +1,9 @@
+#![no_std]
+#![doc = include_str!("../README.md")]
+
+#[doc(hidden)]
+pub mod runtime;
+
+// Re-export everything
+pub use embedded_eventloop::*;
+pub use runtime::{send_event_default, wait_for_event_default};
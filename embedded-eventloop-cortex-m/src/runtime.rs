@@ -0,0 +1,49 @@
+//! Provides the runtime specific functions for a Cortex-M platform
+
+use cortex_m::asm;
+
+/// Blocks until an event occurs via `wfe` (may wake spuriously), or until an interrupt occurs via `wfi` if the
+/// `wfi-sleep` feature is enabled
+///
+/// This is the built-in wakeup primitive, which can be called by a custom wakeup primitive as fallback (see the
+/// `custom-wakeup` feature).
+pub fn wait_for_event_default() {
+    #[cfg(not(feature = "wfi-sleep"))]
+    asm::wfe();
+    #[cfg(feature = "wfi-sleep")]
+    asm::wfi();
+}
+/// Raises an event via `sev`, or does nothing if the `wfi-sleep` feature is enabled, since `sev` cannot wake a `wfi`
+///
+/// This is the built-in wakeup primitive, which can be called by a custom wakeup primitive as fallback (see the
+/// `custom-wakeup` feature).
+pub fn send_event_default() {
+    #[cfg(not(feature = "wfi-sleep"))]
+    asm::sev();
+}
+
+/// Blocks until an event occurs (may wake spuriously); should be similar to ARM's `wfe` instruction
+#[no_mangle]
+#[doc(hidden)]
+#[allow(non_snake_case)]
+#[cfg(not(feature = "custom-wakeup"))]
+pub fn _runtime_waitforevent_r3iRR3iR() {
+    wait_for_event_default();
+}
+
+/// Raises an event; should be similar to ARM's `sev` instruction
+#[no_mangle]
+#[doc(hidden)]
+#[allow(non_snake_case)]
+#[cfg(not(feature = "custom-wakeup"))]
+pub fn _runtime_sendevent_ZMWrWpGO() {
+    send_event_default();
+}
+
+/// Ensures that `code` is run exclusively, without being subject to race conditions or interrupts
+#[no_mangle]
+#[doc(hidden)]
+#[allow(non_snake_case)]
+pub fn _runtime_threadsafe_NfpNM21J(code: &mut dyn FnMut()) {
+    critical_section::with(|_| code())
+}
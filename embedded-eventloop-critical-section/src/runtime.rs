@@ -0,0 +1,43 @@
+//! Provides the runtime specific functions for any platform with a `critical-section` implementation
+
+/// Blocks until an event occurs (may wake spuriously); should be similar to ARM's `wfe` instruction
+#[no_mangle]
+#[doc(hidden)]
+#[allow(non_snake_case)]
+#[cfg(target_arch = "arm")]
+pub fn _runtime_waitforevent_r3iRR3iR() {
+    cortex_m::asm::wfe();
+}
+/// Wakes spuriously after a spin-loop hint, since there is no portable way to wait for an event
+#[no_mangle]
+#[doc(hidden)]
+#[allow(non_snake_case)]
+#[cfg(not(target_arch = "arm"))]
+pub fn _runtime_waitforevent_r3iRR3iR() {
+    core::hint::spin_loop();
+}
+
+/// Raises an event; should be similar to ARM's `sev` instruction
+#[no_mangle]
+#[doc(hidden)]
+#[allow(non_snake_case)]
+#[cfg(target_arch = "arm")]
+pub fn _runtime_sendevent_ZMWrWpGO() {
+    cortex_m::asm::sev();
+}
+/// No-op, since the portable wait never blocks
+#[no_mangle]
+#[doc(hidden)]
+#[allow(non_snake_case)]
+#[cfg(not(target_arch = "arm"))]
+pub fn _runtime_sendevent_ZMWrWpGO() {
+    // No-op
+}
+
+/// Ensures that `code` is run exclusively, without being subject to race conditions or interrupts
+#[no_mangle]
+#[doc(hidden)]
+#[allow(non_snake_case)]
+pub fn _runtime_threadsafe_NfpNM21J(code: &mut dyn FnMut()) {
+    critical_section::with(|_| code())
+}
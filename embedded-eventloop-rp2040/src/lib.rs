@@ -1,8 +1,7 @@
 #![no_std]
 #![doc = include_str!("../README.md")]
 
-#[doc(hidden)]
-pub mod runtime;
+pub mod multicore;
 
-// Re-export everything
-pub use embedded_eventloop::*;
+// Re-export everything; the rp2040 uses the generic Cortex-M runtime
+pub use embedded_eventloop_cortex_m::*;
@@ -0,0 +1,119 @@
+//! Cross-core event loops via the inter-core SIO FIFO
+
+use crate::EventLoop;
+use core::ptr;
+use cortex_m::asm;
+
+/// The base address of the single-cycle IO block
+const SIO_BASE: usize = 0xd000_0000;
+/// The register that contains the index of the current core
+const SIO_CPUID: *const u32 = SIO_BASE as *const u32;
+/// The status register of the inter-core FIFO
+const SIO_FIFO_ST: *const u32 = (SIO_BASE + 0x50) as *const u32;
+/// The write register of the inter-core FIFO (writes to the sibling core's RX FIFO)
+const SIO_FIFO_WR: *mut u32 = (SIO_BASE + 0x54) as *mut u32;
+/// The read register of the inter-core FIFO (reads from this core's RX FIFO)
+const SIO_FIFO_RD: *const u32 = (SIO_BASE + 0x58) as *const u32;
+/// The status bit that indicates that the RX FIFO is not empty
+const SIO_FIFO_ST_VLD: u32 = 1 << 0;
+/// The status bit that indicates that the TX FIFO is not full
+const SIO_FIFO_ST_RDY: u32 = 1 << 1;
+
+/// The token that is pushed through the FIFO to wake the sibling core
+pub const WAKEUP_TOKEN: u32 = 0x4556_4e54;
+
+/// A pair of event loops, one per core, that can send events across cores
+///
+/// # Note on cross-core events
+/// The event loops live in shared memory (usually a `static`), so an event is moved across cores by pushing it into the
+/// backlog of the sibling core's event loop. Afterwards, a [`WAKEUP_TOKEN`] is pushed through the inter-core SIO FIFO,
+/// which wakes the sibling core from `wfe` and raises its `SIO_IRQ_PROC` interrupt.
+///
+/// # Note on memory ordering
+/// The backlogs are only accessed within the threadsafe context, which must therefore exclude the sibling core as well;
+/// i.e. the registered `critical-section` implementation must be multicore-safe (e.g. the hardware spinlock based
+/// implementation of `rp2040-hal`). The event must be visible to the sibling core before it is woken up: the critical
+/// section publishes the event, and a `dmb` before pushing the token ensures that the token cannot overtake it. The
+/// sibling core only reads the backlog within the critical section, which acquires the published event.
+///
+/// # Note on the FIFO
+/// Once both cores are running, the FIFO must not be used for anything else, since pending FIFO entries are discarded
+/// by [`drain_wakeup_tokens`]. If `wfi-sleep` is enabled, the sibling core must enable its `SIO_IRQ_PROC` interrupt and
+/// call [`drain_wakeup_tokens`] from the interrupt handler, since the interrupt is asserted as long as the FIFO is not
+/// empty.
+#[derive(Debug)]
+pub struct MulticoreEventLoop<
+    const STACKBOX_SIZE: usize = 64,
+    const BACKLOG_MAX: usize = 32,
+    const LISTENERS_MAX: usize = 32,
+    const PRIORITIES: usize = 1,
+> {
+    /// The event loops, indexed by core
+    eventloops: [EventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX, PRIORITIES>; 2],
+}
+impl<const STACKBOX_SIZE: usize, const BACKLOG_MAX: usize, const LISTENERS_MAX: usize, const PRIORITIES: usize>
+    MulticoreEventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX, PRIORITIES>
+{
+    /// Creates a new pair of event loops
+    pub const fn new() -> Self {
+        Self { eventloops: [EventLoop::new(), EventLoop::new()] }
+    }
+
+    /// The event loop of the current core
+    pub fn local(&self) -> &EventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX, PRIORITIES> {
+        &self.eventloops[current_core()]
+    }
+    /// The event loop of the sibling core
+    pub fn sibling(&self) -> &EventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX, PRIORITIES> {
+        &self.eventloops[current_core() ^ 1]
+    }
+
+    /// Sends an event to the event loop of the sibling core and wakes the sibling core, returns `Err(event)` if the
+    /// backlog is reached
+    pub fn cross_core_send<T>(&self, event: T) -> Result<(), T>
+    where
+        T: Send + 'static,
+    {
+        self.sibling().send(event)?;
+        push_wakeup_token();
+        Ok(())
+    }
+}
+impl<const STACKBOX_SIZE: usize, const BACKLOG_MAX: usize, const LISTENERS_MAX: usize, const PRIORITIES: usize> Default
+    for MulticoreEventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX, PRIORITIES>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The index of the current core
+pub fn current_core() -> usize {
+    let cpuid = unsafe { ptr::read_volatile(SIO_CPUID) };
+    cpuid as usize
+}
+
+/// Pushes a wakeup token to the sibling core
+///
+/// If the FIFO is full, the sibling core already has pending tokens that will wake it up, so the token is not pushed.
+pub fn push_wakeup_token() {
+    // Ensure that all writes are visible to the sibling core before it can observe the token
+    asm::dmb();
+    let status = unsafe { ptr::read_volatile(SIO_FIFO_ST) };
+    if status & SIO_FIFO_ST_RDY != 0 {
+        unsafe { ptr::write_volatile(SIO_FIFO_WR, WAKEUP_TOKEN) };
+    }
+
+    // The FIFO write does not raise an event by itself
+    asm::sev();
+}
+
+/// Drains all pending wakeup tokens of the current core and returns the amount of drained tokens
+pub fn drain_wakeup_tokens() -> usize {
+    let mut drained = 0;
+    while unsafe { ptr::read_volatile(SIO_FIFO_ST) } & SIO_FIFO_ST_VLD != 0 {
+        let _token = unsafe { ptr::read_volatile(SIO_FIFO_RD) };
+        drained += 1;
+    }
+    drained
+}
@@ -0,0 +1,8 @@
+#![doc = include_str!("../README.md")]
+
+#[doc(hidden)]
+pub mod runtime;
+pub mod testing;
+
+// Re-export everything
+pub use embedded_eventloop::*;
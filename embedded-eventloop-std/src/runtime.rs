@@ -0,0 +1,77 @@
+//! Provides the runtime specific functions for a std-based host
+
+use std::{
+    cell::Cell,
+    sync::{Condvar, Mutex, MutexGuard},
+    thread::{self, ThreadId},
+};
+
+/// The amount of raised events
+static EVENTS: Mutex<u64> = Mutex::new(0);
+/// Signals that an event has been raised
+static EVENTS_RAISED: Condvar = Condvar::new();
+/// The owning thread and the nesting depth of the critical section
+static CRITICAL: Mutex<(Option<ThreadId>, usize)> = Mutex::new((None, 0));
+/// Signals that the critical section has been released
+static CRITICAL_RELEASED: Condvar = Condvar::new();
+
+thread_local! {
+    /// The amount of events this thread has already seen; emulates a per-core event register
+    static EVENTS_SEEN: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Locks a mutex, ignoring poisoning by panicking tests
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Blocks until an event has been raised since the last call on this thread
+#[no_mangle]
+#[doc(hidden)]
+#[allow(non_snake_case)]
+pub fn _runtime_waitforevent_r3iRR3iR() {
+    let seen = EVENTS_SEEN.get();
+    let events = EVENTS_RAISED.wait_while(lock(&EVENTS), |events| *events == seen);
+    let events = events.unwrap_or_else(|poisoned| poisoned.into_inner());
+    EVENTS_SEEN.set(*events);
+}
+
+/// Raises an event for all threads
+#[no_mangle]
+#[doc(hidden)]
+#[allow(non_snake_case)]
+pub fn _runtime_sendevent_ZMWrWpGO() {
+    *lock(&EVENTS) += 1;
+    EVENTS_RAISED.notify_all();
+}
+
+/// Runs `code` in a reentrant, process-wide critical section
+#[no_mangle]
+#[doc(hidden)]
+#[allow(non_snake_case)]
+pub fn _runtime_threadsafe_NfpNM21J(code: &mut dyn FnMut()) {
+    /// Releases the critical section, even if `code` panics
+    struct Release;
+    impl Drop for Release {
+        fn drop(&mut self) {
+            let mut critical = lock(&CRITICAL);
+            critical.1 -= 1;
+            if critical.1 == 0 {
+                critical.0 = None;
+                CRITICAL_RELEASED.notify_one();
+            }
+        }
+    }
+
+    // Acquire the critical section
+    let this = thread::current().id();
+    let critical = lock(&CRITICAL);
+    let critical = CRITICAL_RELEASED.wait_while(critical, |(owner, _)| owner.is_some_and(|owner| owner != this));
+    let mut critical = critical.unwrap_or_else(|poisoned| poisoned.into_inner());
+    *critical = (Some(this), critical.1 + 1);
+    drop(critical);
+
+    // Run the code
+    let _release = Release;
+    code();
+}
@@ -0,0 +1,63 @@
+//! A deterministic runtime that records its invocations, e.g. to verify the wakeup discipline of an event loop
+
+use crate::runtime::_runtime_threadsafe_NfpNM21J;
+use embedded_eventloop::runtime::Runtime;
+use std::{cell::RefCell, mem};
+
+/// A recorded runtime invocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RuntimeCall {
+    /// A call to [`Runtime::wait_for_event`]
+    WaitForEvent,
+    /// A call to [`Runtime::send_event`]
+    SendEvent,
+    /// A call to [`Runtime::with_critical_section`], including nested calls
+    CriticalSection,
+}
+
+thread_local! {
+    /// The recorded invocations of the current thread
+    static CALLS: RefCell<Vec<RuntimeCall>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A deterministic runtime that records each invocation
+///
+/// # Note on determinism
+/// [`Runtime::wait_for_event`] returns immediately instead of blocking, which is a valid spurious wakeup; so event
+/// loops using this runtime must be driven via non-blocking methods or with a stop condition (e.g.
+/// `EventLoop::enter_until`). The critical section is the same process-wide reentrant lock as for the linked std
+/// runtime.
+///
+/// # Note on threads
+/// The invocations are recorded per thread, so that tests that run in parallel do not interfere with each other.
+/// Invocations from other threads (e.g. an event that is sent from a spawned thread) are recorded for these threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct TestRuntime;
+impl TestRuntime {
+    /// The invocations that have been recorded on the current thread, oldest first
+    pub fn calls() -> Vec<RuntimeCall> {
+        CALLS.with_borrow(|calls| calls.clone())
+    }
+    /// Takes the invocations that have been recorded on the current thread, oldest first, and clears the record
+    pub fn take_calls() -> Vec<RuntimeCall> {
+        CALLS.with_borrow_mut(mem::take)
+    }
+
+    /// Records an invocation
+    fn record(call: RuntimeCall) {
+        CALLS.with_borrow_mut(|calls| calls.push(call));
+    }
+}
+impl Runtime for TestRuntime {
+    fn wait_for_event() {
+        Self::record(RuntimeCall::WaitForEvent);
+    }
+    fn send_event() {
+        Self::record(RuntimeCall::SendEvent);
+    }
+    fn with_critical_section(code: &mut dyn FnMut()) {
+        // Record the call before running the code, so that nested calls are recorded in order
+        Self::record(RuntimeCall::CriticalSection);
+        _runtime_threadsafe_NfpNM21J(code);
+    }
+}
@@ -2,19 +2,127 @@
 
 use core::{
     any::TypeId,
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+    hash::{Hash, Hasher},
     mem::{self, MaybeUninit},
-    ptr,
+    ptr, slice,
 };
 
+/// The alignment the box storage guarantees for boxed values
+pub const ALIGN: usize = mem::align_of::<Bytes<0>>();
+
+/// Opaque value bytes, over-aligned so that the boxed value can be accessed in-place
+///
+/// # Note on initialization
+/// The bytes are possibly uninitialized, since a copied value may contain padding bytes, and the bytes after the value
+/// are never written; so they must never be read as `u8` unless it is known that they are initialized.
+#[derive(Clone, Copy)]
+#[repr(C, align(16))]
+struct Bytes<const SIZE: usize>([MaybeUninit<u8>; SIZE]);
+impl<const SIZE: usize> Bytes<SIZE> {
+    /// Creates new uninitialized bytes
+    const fn uninit() -> Self {
+        Self([MaybeUninit::uninit(); SIZE])
+    }
+
+    /// Gets the first `len` bytes as initialized bytes
+    ///
+    /// # Safety
+    /// The first `len` bytes must be initialized, i.e. they must be a copy of a value without padding bytes.
+    const unsafe fn assume_init(&self, len: usize) -> &[u8] {
+        assert!(len <= SIZE, "length exceeds stackbox size");
+        unsafe { slice::from_raw_parts(self.0.as_ptr() as *const u8, len) }
+    }
+}
+impl<const SIZE: usize> Debug for Bytes<SIZE> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // The bytes are possibly uninitialized, so they cannot be printed
+        f.debug_struct("Bytes").field("size", &SIZE).finish_non_exhaustive()
+    }
+}
+
+/// The type name of a boxed value; zero-sized if the `type-name` feature is disabled
+#[derive(Clone, Copy)]
+struct TypeName {
+    /// The type name
+    #[cfg(feature = "type-name")]
+    name: &'static str,
+}
+impl TypeName {
+    /// Gets the type name of `T`
+    #[cfg_attr(not(feature = "type-name"), allow(clippy::extra_unused_type_parameters))]
+    fn of<T>() -> Self {
+        Self {
+            #[cfg(feature = "type-name")]
+            name: core::any::type_name::<T>(),
+        }
+    }
+    /// Gets an unknown type name, e.g. for values that have been reconstructed from their raw bytes
+    const fn unknown() -> Self {
+        Self {
+            #[cfg(feature = "type-name")]
+            name: "<unknown>",
+        }
+    }
+
+    /// The type name, or `"<unknown>"` if the `type-name` feature is disabled
+    #[cfg(feature = "type-name")]
+    const fn get(&self) -> &'static str {
+        self.name
+    }
+    /// The type name, or `"<unknown>"` if the `type-name` feature is disabled
+    #[cfg(not(feature = "type-name"))]
+    const fn get(&self) -> &'static str {
+        "<unknown>"
+    }
+}
+impl Debug for TypeName {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Debug::fmt(self.get(), f)
+    }
+}
+
+/// An error that is returned if a value does not fit into a box
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoxError {
+    /// The size of the value
+    pub needed: usize,
+    /// The size of the box
+    pub available: usize,
+}
+impl Display for BoxError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "value of {} bytes does not fit into stackbox of {} bytes", self.needed, self.available)
+    }
+}
+impl Error for BoxError {}
+
 /// A stack-allocated type-opaque box
+///
+/// # Note on zero-sized values
+/// Zero-sized values (e.g. marker events like `struct Tick;`) are boxed without copying any data, so they fit into
+/// every box, including a box with `SIZE == 0`. The box type itself still has a fixed size of `SIZE` bytes though.
+///
+/// # Note on code size
+/// The value is copied in and out of the box by a single size-driven copy routine that is shared by all value types;
+/// per value type, only a thin wrapper that checks the type ID is generated. A destructor is only generated for types
+/// that actually need to be dropped (see [`mem::needs_drop`]), so boxing plain old data values does not add any
+/// type-specific drop glue.
 #[derive(Debug)]
 pub struct Box<const SIZE: usize> {
     /// The type info
     type_id: TypeId,
+    /// The type name for debugging purposes
+    type_name: TypeName,
     /// The opaque bytes of the value
-    bytes: [u8; SIZE],
+    bytes: Bytes<SIZE>,
+    /// The size of the value in bytes
+    len: usize,
     /// A destructor to drop the value
-    drop: Option<fn(TypeId, [u8; SIZE])>,
+    drop: Option<fn(TypeId, Bytes<SIZE>)>,
+    /// A function to clone the value if the box has been created as cloneable
+    clone: Option<fn(TypeId, &Bytes<SIZE>) -> Bytes<SIZE>>,
 }
 impl<const SIZE: usize> Box<SIZE> {
     /// Creates a new stackbox with the given `value`, returns `Err(value)` if the value is larger than `SIZE`
@@ -23,19 +131,160 @@ impl<const SIZE: usize> Box<SIZE> {
         T: 'static,
     {
         // Validate that `T` fits into the box
-        if mem::size_of::<T>() > SIZE {
+        debug_assert!(mem::align_of::<T>() <= ALIGN, "type alignment exceeds stackbox alignment");
+        if !Self::fits::<T>() {
             return Err(value);
         };
 
         // Wrap the value
         let (type_id, bytes) = value_into_bytes(value);
-        Ok(Self { type_id, bytes, drop: Some(Self::drop_impl::<T>) })
+        let (type_name, len) = (TypeName::of::<T>(), mem::size_of::<T>());
+        let drop = match mem::needs_drop::<T>() {
+            true => Some(Self::drop_impl::<T> as fn(TypeId, Bytes<SIZE>)),
+            false => None,
+        };
+        Ok(Self { type_id, type_name, bytes, len, drop, clone: None })
+    }
+    /// Creates a new stackbox with the given `value`, returns an error describing the size mismatch if the value is
+    /// larger than `SIZE`; the value is dropped in this case
+    pub fn try_new<T>(value: T) -> Result<Self, BoxError>
+    where
+        T: 'static,
+    {
+        Self::new(value).map_err(|_| BoxError { needed: mem::size_of::<T>(), available: SIZE })
+    }
+    /// Creates a new stackbox with the given `value`; fails to compile if the value is larger than `SIZE`
+    pub fn new_checked<T>(value: T) -> Self
+    where
+        T: 'static,
+    {
+        const { assert!(mem::size_of::<T>() <= SIZE, "type is too large for stackbox") };
+        Self::new(value).unwrap_or_else(|_| unreachable!("failed to box value"))
+    }
+    /// Creates a new cloneable stackbox with the given `value`, returns `Err(value)` if the value is larger than `SIZE`
+    pub fn new_cloneable<T>(value: T) -> Result<Self, T>
+    where
+        T: Clone + 'static,
+    {
+        let mut this = Self::new(value)?;
+        this.clone = Some(Self::clone_impl::<T>);
+        Ok(this)
+    }
+    /// Reconstructs a stackbox from the raw bytes of a value and its type ID, returns `None` if `bytes` is larger than
+    /// `SIZE`
+    ///
+    /// The reconstructed box has no destructor, is not cloneable and has an unknown type name.
+    ///
+    /// # Safety
+    /// The caller must ensure that `bytes` is a valid value of the type identified by `type_id` (e.g. as returned by
+    /// [`Self::as_bytes`]), and that this type is plain old data, i.e. it is `Copy`, does not contain any references or
+    /// pointers whose validity depends on the context, and every byte pattern of its size is a valid value.
+    pub unsafe fn from_bytes(type_id: TypeId, bytes: &[u8]) -> Option<Self> {
+        // Validate that the value fits into the box
+        if bytes.len() > SIZE {
+            return None;
+        }
+
+        // Copy the bytes
+        let (mut value_bytes, len) = (Bytes::uninit(), bytes.len());
+        unsafe { value_bytes.0.as_mut_ptr().copy_from_nonoverlapping(bytes.as_ptr() as *const MaybeUninit<u8>, len) };
+        Some(Self { type_id, type_name: TypeName::unknown(), bytes: value_bytes, len, drop: None, clone: None })
+    }
+
+    /// The size of the box storage in bytes (i.e. `SIZE`)
+    pub const fn size_of() -> usize {
+        SIZE
+    }
+    /// Whether a value of type `T` fits into the box
+    pub const fn fits<T>() -> bool {
+        mem::size_of::<T>() <= SIZE
     }
 
     /// The type ID of the inner value
-    pub fn inner_type_id(&self) -> TypeId {
+    pub const fn inner_type_id(&self) -> TypeId {
         self.type_id
     }
+    /// The type name of the inner value for debugging purposes, or `"<unknown>"` if the `type-name` feature is disabled
+    pub const fn type_name(&self) -> &'static str {
+        self.type_name.get()
+    }
+    /// Whether the inner value is of type `T`
+    pub fn is<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.type_id == TypeId::of::<T>()
+    }
+
+    /// Gets a reference to the wrapped value, returns `None` if the value is not of type `T`
+    ///
+    /// # Panics
+    /// The value is accessed in-place, so this function panics if the alignment of `T` exceeds [`ALIGN`].
+    pub fn as_ref<T>(&self) -> Option<&T>
+    where
+        T: 'static,
+    {
+        // Validate that we have boxed a type `T`
+        if TypeId::of::<T>() != self.type_id {
+            return None;
+        }
+
+        // Reinterpret the bytes
+        let value_ptr = self.bytes.0.as_ptr() as *const T;
+        assert!(value_ptr.is_aligned(), "stackbox storage is not aligned for type");
+        unsafe { value_ptr.as_ref() }
+    }
+    /// Gets a mutable reference to the wrapped value, returns `None` if the value is not of type `T`
+    ///
+    /// # Panics
+    /// The value is accessed in-place, so this function panics if the alignment of `T` exceeds [`ALIGN`].
+    pub fn as_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: 'static,
+    {
+        // Validate that we have boxed a type `T`
+        if TypeId::of::<T>() != self.type_id {
+            return None;
+        }
+
+        // Reinterpret the bytes
+        let value_ptr = self.bytes.0.as_mut_ptr() as *mut T;
+        assert!(value_ptr.is_aligned(), "stackbox storage is not aligned for type");
+        unsafe { value_ptr.as_mut() }
+    }
+
+    /// The raw bytes of the boxed value, e.g. to persist or transmit plain old data values
+    ///
+    /// # Safety
+    /// The caller must ensure that every byte of the boxed value is initialized, i.e. that the value type has no
+    /// padding bytes and does not contain uninitialized memory (e.g. `MaybeUninit` fields or unions); reading padding
+    /// bytes is undefined behavior.
+    pub const unsafe fn as_bytes(&self) -> &[u8] {
+        unsafe { self.bytes.assume_init(self.len) }
+    }
+    /// Copies the boxed value bytewise, including possibly uninitialized padding bytes
+    ///
+    /// The copy has no destructor and is cloneable by copying it bytewise again.
+    ///
+    /// # Safety
+    /// The caller must ensure that the boxed value is `Copy`.
+    pub(crate) const unsafe fn copy_unchecked(&self) -> Self {
+        let (type_id, type_name, bytes, len) = (self.type_id, self.type_name, self.bytes, self.len);
+        Self { type_id, type_name, bytes, len, drop: None, clone: Some(Self::copy_impl) }
+    }
+
+    /// Whether the box has been created via [`Self::new_cloneable`] or is a snapshot of a `Copy` value, and can be
+    /// cloned
+    pub const fn is_cloneable(&self) -> bool {
+        self.clone.is_some()
+    }
+    /// Clones the box and the wrapped value, returns `None` if the box has not been created via [`Self::new_cloneable`]
+    pub fn try_clone(&self) -> Option<Self> {
+        let clone = self.clone?;
+        let bytes = clone(self.type_id, &self.bytes);
+        let (type_id, type_name, len) = (self.type_id, self.type_name, self.len);
+        Some(Self { type_id, type_name, bytes, len, drop: self.drop, clone: Some(clone) })
+    }
 
     /// Unwraps the underlying wrapped value, return `Err(self)` if the value is not of type `T`
     pub fn into_inner<T>(mut self) -> Result<T, Self>
@@ -53,14 +302,60 @@ impl<const SIZE: usize> Box<SIZE> {
         Ok(value)
     }
 
+    /// Creates a matcher to unwrap the underlying value as one of several types, e.g. to route events without an enum
+    ///
+    /// The box is consumed exactly once by the first matching case:
+    /// `boxed.match_take().case(|a: A| ...).case(|b: B| ...).otherwise(|boxed| ...)`.
+    pub fn match_take<FR>(self) -> BoxMatcher<SIZE, FR> {
+        BoxMatcher { state: Err(self) }
+    }
+
+    /// Unwraps the underlying value of type `A`, transforms it via `f` and boxes the result of type `B`, returns
+    /// `Err(self)` if the value is not of type `A` or if `B` is larger than `SIZE`
+    pub fn map<A, B, F>(self, f: F) -> Result<Self, Self>
+    where
+        A: 'static,
+        B: 'static,
+        F: FnOnce(A) -> B,
+    {
+        // Validate that the result fits into the box before the value is unwrapped
+        if !Self::fits::<B>() {
+            return Err(self);
+        }
+
+        // Transform the value
+        let value: A = self.into_inner()?;
+        let boxed = Self::new(f(value)).unwrap_or_else(|_| unreachable!("failed to box mapped value"));
+        Ok(boxed)
+    }
+
     /// Safely unwraps a value of type `T` and drops it
-    fn drop_impl<T>(type_id: TypeId, bytes: [u8; SIZE])
+    fn drop_impl<T>(type_id: TypeId, bytes: Bytes<SIZE>)
     where
         T: 'static,
     {
         let value: T = bytes_into_value(type_id, bytes);
         drop(value);
     }
+    /// Copies the bytes of a `Copy` value
+    fn copy_impl(_type_id: TypeId, bytes: &Bytes<SIZE>) -> Bytes<SIZE> {
+        *bytes
+    }
+    /// Safely clones a value of type `T` in-place and returns the bytes of the clone
+    fn clone_impl<T>(type_id: TypeId, bytes: &Bytes<SIZE>) -> Bytes<SIZE>
+    where
+        T: Clone + 'static,
+    {
+        // Validate constraints
+        assert_eq!(type_id, TypeId::of::<T>(), "type mismatch");
+
+        // Reinterpret the bytes and clone the value
+        let value_ptr = bytes.0.as_ptr() as *const T;
+        assert!(value_ptr.is_aligned(), "stackbox storage is not aligned for type");
+        let value = unsafe { &*value_ptr }.clone();
+        let (_, bytes) = value_into_bytes(value);
+        bytes
+    }
 }
 impl<const SIZE: usize> Drop for Box<SIZE> {
     fn drop(&mut self) {
@@ -70,14 +365,133 @@ impl<const SIZE: usize> Drop for Box<SIZE> {
         }
     }
 }
+#[cfg(feature = "defmt")]
+impl<const SIZE: usize> defmt::Format for Box<SIZE> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "Box {{ type_name: {=str}, size: {=usize} }}", self.type_name(), SIZE);
+    }
+}
+
+/// A matcher that unwraps a box as the first matching type of several cases, see [`Box::match_take`]
+#[derive(Debug)]
+pub struct BoxMatcher<const SIZE: usize, FR> {
+    /// The result of the matching case, or the box if no case has matched yet
+    state: Result<FR, Box<SIZE>>,
+}
+impl<const SIZE: usize, FR> BoxMatcher<SIZE, FR> {
+    /// Unwraps the value and calls `f` if no previous case has matched and the value is of type `T`
+    pub fn case<T, F>(self, f: F) -> Self
+    where
+        T: 'static,
+        F: FnOnce(T) -> FR,
+    {
+        let state = match self.state {
+            Err(boxed) => boxed.into_inner().map(f),
+            matched => matched,
+        };
+        Self { state }
+    }
+    /// Returns the result of the matching case, or calls `f` with the box if no case has matched
+    pub fn otherwise<F>(self, f: F) -> FR
+    where
+        F: FnOnce(Box<SIZE>) -> FR,
+    {
+        self.state.unwrap_or_else(f)
+    }
+    /// Returns the result of the matching case, or `Err(box)` if no case has matched
+    pub fn finish(self) -> Result<FR, Box<SIZE>> {
+        self.state
+    }
+}
+
+/// A stack-allocated type-opaque box that provides access to the value as trait object `Dyn` (e.g. `dyn Trait`)
+pub struct DynBox<Dyn, const SIZE: usize>
+where
+    Dyn: ?Sized + 'static,
+{
+    /// The boxed value
+    inner: Box<SIZE>,
+    /// The type-erased function to coerce a reference to the value into a trait object reference
+    coerce: fn(),
+    /// A type specific implementation to get the value as trait object
+    as_dyn: fn(&Box<SIZE>, fn()) -> &Dyn,
+}
+impl<Dyn, const SIZE: usize> DynBox<Dyn, SIZE>
+where
+    Dyn: ?Sized + 'static,
+{
+    /// Creates a new stackbox with the given `value` and a function to coerce a reference to the value into a trait
+    /// object reference (usually `|value| value`), returns `Err(value)` if the value is larger than `SIZE`
+    pub fn new<T>(value: T, coerce: fn(&T) -> &Dyn) -> Result<Self, T>
+    where
+        T: 'static,
+    {
+        let inner = Box::new(value)?;
+        let coerce = unsafe { mem::transmute::<fn(&T) -> &Dyn, fn()>(coerce) };
+        Ok(Self { inner, coerce, as_dyn: Self::as_dyn_impl::<T> })
+    }
+
+    /// The type ID of the inner value
+    pub const fn inner_type_id(&self) -> TypeId {
+        self.inner.inner_type_id()
+    }
+    /// The type name of the inner value for debugging purposes, or `"<unknown>"` if the `type-name` feature is disabled
+    pub const fn type_name(&self) -> &'static str {
+        self.inner.type_name()
+    }
+
+    /// Gets a reference to the wrapped value as trait object
+    pub fn as_dyn(&self) -> &Dyn {
+        (self.as_dyn)(&self.inner, self.coerce)
+    }
+
+    /// Unwraps the underlying wrapped value, return `Err(self)` if the value is not of type `T`
+    pub fn into_inner<T>(self) -> Result<T, Self>
+    where
+        T: 'static,
+    {
+        let Self { inner, coerce, as_dyn } = self;
+        inner.into_inner().map_err(|inner| Self { inner, coerce, as_dyn })
+    }
+
+    /// Recovers the coerce function for a value of type `T` and gets the value as trait object
+    fn as_dyn_impl<T>(inner: &Box<SIZE>, coerce: fn()) -> &Dyn
+    where
+        T: 'static,
+    {
+        let coerce = unsafe { mem::transmute::<fn(), fn(&T) -> &Dyn>(coerce) };
+        let value = inner.as_ref::<T>().expect("type mismatch");
+        coerce(value)
+    }
+}
+impl<Dyn, const SIZE: usize> Debug for DynBox<Dyn, SIZE>
+where
+    Dyn: ?Sized + 'static,
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("DynBox").field("inner", &self.inner).finish()
+    }
+}
 
 /// A stack-allocated type-opaque box for copyable values
+///
+/// # Note on comparison
+/// Boxes that have been created via [`Self::new_comparable`] are compared and hashed by their type and value, using the
+/// `PartialEq` and `Hash` implementations of the value type. The value bytes are never compared directly, since they
+/// may contain uninitialized padding bytes. All other boxes are only equal to themselves, i.e. they are compared by
+/// identity.
 #[derive(Debug, Clone, Copy)]
 pub struct CopyBox<const SIZE: usize> {
     /// The type info
     type_id: TypeId,
+    /// The type name for debugging purposes
+    type_name: TypeName,
     /// The opaque bytes of the value
-    bytes: [u8; SIZE],
+    bytes: Bytes<SIZE>,
+    /// A function to compare the value with another value of the same type if the box has been created as comparable
+    eq: Option<fn(&Bytes<SIZE>, &Bytes<SIZE>) -> bool>,
+    /// A function to hash the value if the box has been created as comparable
+    hash: Option<fn(&Bytes<SIZE>, &mut dyn Hasher)>,
 }
 impl<const SIZE: usize> CopyBox<SIZE> {
     /// Creates a new stackbox with the given `value`, returns `None` if the value is larger than `SIZE`
@@ -86,19 +500,41 @@ impl<const SIZE: usize> CopyBox<SIZE> {
         T: 'static,
     {
         // Validate that `T` fits into the box
+        debug_assert!(mem::align_of::<T>() <= ALIGN, "type alignment exceeds stackbox alignment");
         if mem::size_of::<T>() > SIZE {
             return None;
         };
 
         // Wrap the value
         let (type_id, bytes) = value_into_bytes(value);
-        Some(Self { type_id, bytes })
+        Some(Self { type_id, type_name: TypeName::of::<T>(), bytes, eq: None, hash: None })
+    }
+    /// Creates a new comparable stackbox with the given `value`, returns `None` if the value is larger than `SIZE`
+    pub fn new_comparable<T>(value: T) -> Option<Self>
+    where
+        T: Eq + Hash + 'static,
+    {
+        let mut this = Self::new(value)?;
+        this.eq = Some(Self::eq_impl::<T>);
+        this.hash = Some(Self::hash_impl::<T>);
+        Some(this)
     }
 
     /// The type ID of the inner value
-    pub fn inner_type_id(&self) -> TypeId {
+    pub const fn inner_type_id(&self) -> TypeId {
         self.type_id
     }
+    /// The type name of the inner value for debugging purposes, or `"<unknown>"` if the `type-name` feature is disabled
+    pub const fn type_name(&self) -> &'static str {
+        self.type_name.get()
+    }
+    /// Whether the inner value is of type `T`
+    pub fn is<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.type_id == TypeId::of::<T>()
+    }
 
     /// Unwraps the underlying wrapped value, return `Err(self)` if the value is not of type `T`
     pub fn inner<T>(&self) -> Option<T>
@@ -114,40 +550,115 @@ impl<const SIZE: usize> CopyBox<SIZE> {
         let value = bytes_into_value(self.type_id, self.bytes);
         Some(value)
     }
+
+    /// Whether the box has been created via [`Self::new_comparable`] and is compared by value
+    pub const fn is_comparable(&self) -> bool {
+        self.eq.is_some()
+    }
+
+    /// Compares two values of type `T` in-place
+    fn eq_impl<T>(bytes: &Bytes<SIZE>, other: &Bytes<SIZE>) -> bool
+    where
+        T: Eq + 'static,
+    {
+        // Reinterpret the bytes and compare the values
+        let (value_ptr, other_ptr) = (bytes.0.as_ptr() as *const T, other.0.as_ptr() as *const T);
+        assert!(value_ptr.is_aligned() && other_ptr.is_aligned(), "stackbox storage is not aligned for type");
+        unsafe { *value_ptr == *other_ptr }
+    }
+    /// Hashes a value of type `T` in-place
+    fn hash_impl<T>(bytes: &Bytes<SIZE>, mut state: &mut dyn Hasher)
+    where
+        T: Hash + 'static,
+    {
+        // Reinterpret the bytes and hash the value
+        let value_ptr = bytes.0.as_ptr() as *const T;
+        assert!(value_ptr.is_aligned(), "stackbox storage is not aligned for type");
+        unsafe { &*value_ptr }.hash(&mut state);
+    }
+}
+impl<const SIZE: usize> PartialEq for CopyBox<SIZE> {
+    /// Compares the types and the values of comparable boxes, or the identity of other boxes
+    fn eq(&self, other: &Self) -> bool {
+        match (self.eq, other.eq) {
+            // Boxes of the same type have been created with the same comparison function
+            (Some(eq), Some(_)) => self.type_id == other.type_id && eq(&self.bytes, &other.bytes),
+            _ => ptr::eq(self, other),
+        }
+    }
+}
+impl<const SIZE: usize> Eq for CopyBox<SIZE> {
+    // Marker trait, no members to implement
+}
+impl<const SIZE: usize> Hash for CopyBox<SIZE> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Boxes that are compared by identity are only equal to themselves, so their type is a valid hash
+        self.type_id.hash(state);
+        if let Some(hash) = self.hash {
+            hash(&self.bytes, state);
+        }
+    }
+}
+#[cfg(feature = "defmt")]
+impl<const SIZE: usize> defmt::Format for CopyBox<SIZE> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "CopyBox {{ type_name: {=str}, size: {=usize} }}", self.type_name(), SIZE);
+    }
 }
 
 /// Safely transforms a value into a byte array
-fn value_into_bytes<T, const SIZE: usize>(value: T) -> (TypeId, [u8; SIZE])
+fn value_into_bytes<T, const SIZE: usize>(value: T) -> (TypeId, Bytes<SIZE>)
 where
     T: 'static,
 {
-    // Validate constraints
-    assert!(mem::size_of::<T>() <= SIZE, "type is too large for stackbox");
-
-    // Copy the value
-    let mut bytes = [0; SIZE];
-    let value_ptr = ptr::addr_of!(value) as *const u8;
-    unsafe { bytes.as_mut_ptr().copy_from_nonoverlapping(value_ptr, mem::size_of::<T>()) };
-
-    // Forget the value and return the data
+    // Copy the value and forget it
+    let bytes = unsafe { copy_into_bytes(ptr::addr_of!(value) as *const u8, mem::size_of::<T>()) };
     mem::forget(value);
     (TypeId::of::<T>(), bytes)
 }
 
 /// Safely recovers a value from a byte array
-fn bytes_into_value<T, const SIZE: usize>(type_id: TypeId, bytes: [u8; SIZE]) -> T
+fn bytes_into_value<T, const SIZE: usize>(type_id: TypeId, bytes: Bytes<SIZE>) -> T
 where
     T: 'static,
 {
     // Validate constraints
-    assert!(mem::size_of::<T>() <= SIZE, "type is too large for stackbox");
     assert_eq!(type_id, TypeId::of::<T>(), "type mismatch");
 
     // Recover the value
-    let mut value = MaybeUninit::uninit();
-    let value_ptr = value.as_mut_ptr() as *mut u8;
-    unsafe { bytes.as_ptr().copy_to_nonoverlapping(value_ptr, mem::size_of::<T>()) };
-
-    // Unwrap the value
+    let mut value = MaybeUninit::<T>::uninit();
+    unsafe { copy_from_bytes(&bytes, value.as_mut_ptr() as *mut u8, mem::size_of::<T>()) };
     unsafe { value.assume_init() }
 }
+
+/// Copies `len` bytes from `value_ptr` into a new byte array; this is not generic over the value type, so it is shared
+/// by all value types
+///
+/// # Safety
+/// `value_ptr` must be valid for reads of `len` bytes.
+unsafe fn copy_into_bytes<const SIZE: usize>(value_ptr: *const u8, len: usize) -> Bytes<SIZE> {
+    // Validate constraints
+    assert!(len <= SIZE, "type is too large for stackbox");
+
+    // Copy the value; zero-sized values carry no data, so there is nothing to copy
+    let mut bytes = Bytes::uninit();
+    if len > 0 {
+        unsafe { bytes.0.as_mut_ptr().copy_from_nonoverlapping(value_ptr as *const MaybeUninit<u8>, len) };
+    }
+    bytes
+}
+
+/// Copies the first `len` bytes of `bytes` to `value_ptr`; this is not generic over the value type, so it is shared by
+/// all value types
+///
+/// # Safety
+/// `value_ptr` must be valid for writes of `len` bytes.
+unsafe fn copy_from_bytes<const SIZE: usize>(bytes: &Bytes<SIZE>, value_ptr: *mut u8, len: usize) {
+    // Validate constraints
+    assert!(len <= SIZE, "type is too large for stackbox");
+
+    // Copy the value; a zero-sized value is fully initialized without copying any data
+    if len > 0 {
+        unsafe { bytes.0.as_ptr().copy_to_nonoverlapping(value_ptr as *mut MaybeUninit<u8>, len) };
+    }
+}
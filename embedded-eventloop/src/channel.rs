@@ -0,0 +1,77 @@
+//! A bounded channel that can be used independently of an event loop, e.g. to pass values from an interrupt handler to
+//! the main task
+
+use crate::{
+    collections::RingBuf,
+    runtime::{LinkedRuntime, Runtime},
+    threadsafe::ThreadSafeCell,
+};
+
+/// A bounded FIFO channel with up to `SIZE` pending values
+///
+/// # Note on blocking
+/// Sending a value raises a hardware event via the runtime (e.g. `sev` on Cortex-M), so a consumer can block in
+/// [`Self::recv`] until a value is available (e.g. via `wfe` on Cortex-M). All accesses happen within the runtime's
+/// critical section, so the channel can be shared between interrupt handlers, threads or cores.
+#[derive(Debug)]
+pub struct Channel<T, const SIZE: usize = 32, R: Runtime = LinkedRuntime> {
+    /// The pending values
+    values: ThreadSafeCell<RingBuf<T, SIZE>, R>,
+}
+impl<T, const SIZE: usize, R> Channel<T, SIZE, R>
+where
+    R: Runtime,
+{
+    /// Creates a new empty channel
+    pub const fn new() -> Self {
+        Self { values: ThreadSafeCell::with_runtime(RingBuf::new()) }
+    }
+
+    /// Sends `value` to the channel, returns `Err(value)` if the channel is full
+    pub fn try_send(&self, value: T) -> Result<(), T> {
+        self.values.scope(|values| values.push(value))?;
+
+        // Trigger a hardware event; this must happen after the value is enqueued to not race with `recv`
+        R::send_event();
+        Ok(())
+    }
+    /// Receives the next pending value, returns `None` if the channel is empty
+    pub fn try_recv(&self) -> Option<T> {
+        self.values.scope(|values| values.pop())
+    }
+    /// Waits until a value is available and receives it
+    pub fn recv(&self) -> T {
+        loop {
+            if let Some(value) = self.try_recv() {
+                return value;
+            }
+
+            // Wait for a hardware event
+            //  - The last action before waiting is the check that the channel is empty. Since `try_send` enqueues
+            //    before it raises the hardware event, and the runtime latches raised events until the next wait
+            //    returns, a value that is sent after this check wakes the wait immediately instead of getting lost.
+            R::wait_for_event();
+        }
+    }
+
+    /// The amount of pending values
+    pub fn len(&self) -> usize {
+        self.values.scope(|values| values.len())
+    }
+    /// Whether no values are pending
+    pub fn is_empty(&self) -> bool {
+        self.values.scope(|values| values.is_empty())
+    }
+    /// Whether the channel is full, so that sending a value would fail
+    pub fn is_full(&self) -> bool {
+        self.values.scope(|values| values.is_full())
+    }
+}
+impl<T, const SIZE: usize, R> Default for Channel<T, SIZE, R>
+where
+    R: Runtime,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
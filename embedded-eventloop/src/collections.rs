@@ -1,22 +1,25 @@
-//! A stack-allocated ring buffer implementation
+//! Stack-allocated collections
+//!
+//! # Note on `const`
+//! The constructors, the queries and the basic `push`/`pop` operations are `const fn`s, so collections can be
+//! prepopulated at compile time, e.g. to initialize a `static`. Iterators, closures and all operations that move
+//! multiple elements at once (e.g. [`Stack::remove`] or [`RingBuf::retain`]) are not available in `const` contexts.
 
-use core::{array::IntoIter, iter::Flatten};
+use core::{
+    array::IntoIter,
+    iter::{Chain, Flatten},
+    mem, slice,
+};
 
-/// A push-only stack-allocated stack for `Copy`-types
+/// A stack-allocated stack
 #[derive(Debug, Clone, Copy)]
-pub struct Stack<T, const SIZE: usize>
-where
-    T: Copy,
-{
+pub struct Stack<T, const SIZE: usize> {
     /// The underlying elements
     elements: [Option<T>; SIZE],
     /// The amount of elements
     len: usize,
 }
-impl<T, const SIZE: usize> Stack<T, SIZE>
-where
-    T: Copy,
-{
+impl<T, const SIZE: usize> Stack<T, SIZE> {
     /// The default value for non-copy const-time initialization
     const INIT: Option<T> = None;
 
@@ -24,24 +27,121 @@ where
     pub const fn new() -> Self {
         Self { elements: [Self::INIT; SIZE], len: 0 }
     }
+    /// Creates a new stack from the elements of `iter`, returns the first element that does not fit if the iterator
+    /// yields more than `SIZE` elements
+    pub fn try_from_iter<I>(iter: I) -> Result<Self, T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut this = Self::new();
+        this.extend(iter)?;
+        Ok(this)
+    }
 
     /// Pushes a value onto the stack
-    pub fn push(&mut self, value: T) -> Result<(), T> {
+    pub const fn push(&mut self, value: T) -> Result<(), T> {
         // Ensure that we have a free slot
         if self.len == SIZE {
             return Err(value);
         }
 
-        // Insert the value
-        self.elements[self.len] = Some(value);
+        // Insert the value; the slot is empty, so forgetting the previous value is a no-op that allows a `const fn`
+        mem::forget(self.elements[self.len].replace(value));
         self.len += 1;
         Ok(())
     }
+    /// Pushes all elements of `iter` onto the stack, returns the first element that does not fit if the stack is full
+    ///
+    /// Elements that have been pushed before the stack became full are kept, and subsequent elements are not consumed
+    /// from the iterator.
+    pub fn extend<I>(&mut self, iter: I) -> Result<(), T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        iter.into_iter().try_for_each(|element| self.push(element))
+    }
+    /// Pops the topmost value from the stack
+    pub const fn pop(&mut self) -> Option<T> {
+        // Ensure that we have an element
+        if self.len == 0 {
+            return None;
+        }
+
+        // Take the value
+        self.len -= 1;
+        self.elements[self.len].take()
+    }
+    /// Removes the element at `index` and replaces it with the topmost element
+    pub fn swap_remove(&mut self, index: usize) -> Option<T> {
+        // Ensure that the element exists
+        if index >= self.len {
+            return None;
+        }
+
+        // Take the element and move the topmost element into the free slot
+        self.len -= 1;
+        self.elements.swap(index, self.len);
+        self.elements[self.len].take()
+    }
+    /// Removes the element at `index` and shifts all subsequent elements down
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        // Ensure that the element exists
+        if index >= self.len {
+            return None;
+        }
+
+        // Take the element and move the free slot to the top
+        let element = self.elements[index].take();
+        self.elements[index..self.len].rotate_left(1);
+        self.len -= 1;
+        element
+    }
+    /// Gets a reference to the element at `index`, counted from the bottom
+    pub const fn get(&self, index: usize) -> Option<&T> {
+        match index < self.len {
+            true => self.elements[index].as_ref(),
+            false => None,
+        }
+    }
+    /// Gets a reference to the topmost element
+    pub const fn last(&self) -> Option<&T> {
+        match self.len {
+            0 => None,
+            len => self.elements[len - 1].as_ref(),
+        }
+    }
+    /// Iterates over the elements from bottom to top without consuming the stack
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.elements[..self.len].iter().flatten()
+    }
+    /// Iterates mutably over the elements from bottom to top without consuming the stack
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.elements[..self.len].iter_mut().flatten()
+    }
+    /// Drops all elements
+    pub fn clear(&mut self) {
+        self.elements[..self.len].iter_mut().for_each(|element| *element = None);
+        self.len = 0;
+    }
+
+    /// The amount of elements
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether the stack is empty
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Whether the stack is full
+    pub const fn is_full(&self) -> bool {
+        self.len == SIZE
+    }
+    /// The maximum amount of elements
+    pub const fn capacity() -> usize {
+        SIZE
+    }
 }
-impl<T, const SIZE: usize> IntoIterator for Stack<T, SIZE>
-where
-    T: Copy,
-{
+impl<T, const SIZE: usize> IntoIterator for Stack<T, SIZE> {
     type Item = T;
     type IntoIter = Flatten<IntoIter<Option<T>, SIZE>>;
 
@@ -49,6 +149,108 @@ where
         self.elements.into_iter().flatten()
     }
 }
+impl<T, const SIZE: usize> Default for Stack<T, SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A stack-allocated slot storage where each element keeps its index until it is removed
+#[derive(Debug, Clone, Copy)]
+pub struct Slots<T, const SIZE: usize> {
+    /// The underlying slots
+    slots: [Option<T>; SIZE],
+}
+impl<T, const SIZE: usize> Slots<T, SIZE> {
+    /// The default value for non-copy const-time initialization
+    const INIT: Option<T> = None;
+
+    /// Creates a new empty slot storage
+    pub const fn new() -> Self {
+        Self { slots: [Self::INIT; SIZE] }
+    }
+
+    /// Inserts `value` into the first free slot and returns the slot index
+    pub fn insert(&mut self, value: T) -> Result<usize, T> {
+        // Find a free slot
+        let Some(index) = self.slots.iter().position(Option::is_none) else {
+            return Err(value);
+        };
+
+        // Insert the value
+        self.slots[index] = Some(value);
+        Ok(index)
+    }
+    /// Inserts `value` into the slot at `index`, returns `Err(value)` if the slot is occupied or out of bounds
+    pub fn insert_at(&mut self, index: usize, value: T) -> Result<(), T> {
+        match self.slots.get_mut(index) {
+            Some(slot @ None) => {
+                *slot = Some(value);
+                Ok(())
+            }
+            _ => Err(value),
+        }
+    }
+    /// Removes the value at `index` and leaves the slot free for reuse
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        self.slots.get_mut(index)?.take()
+    }
+
+    /// Gets a reference to the value at `index`
+    pub const fn get(&self, index: usize) -> Option<&T> {
+        match index < SIZE {
+            true => self.slots[index].as_ref(),
+            false => None,
+        }
+    }
+    /// Gets a mutable reference to the value at `index`
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.slots.get_mut(index)?.as_mut()
+    }
+
+    /// An iterator over the values in slot order
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter().flatten()
+    }
+
+    /// The amount of occupied slots
+    pub const fn len(&self) -> usize {
+        // Iterators are not available in `const` contexts
+        let (mut index, mut len) = (0, 0);
+        while index < SIZE {
+            len += self.slots[index].is_some() as usize;
+            index += 1;
+        }
+        len
+    }
+    /// Whether all slots are free
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+impl<T, const SIZE: usize> AsRef<[Option<T>]> for Slots<T, SIZE> {
+    fn as_ref(&self) -> &[Option<T>] {
+        &self.slots
+    }
+}
+impl<T, const SIZE: usize> AsMut<[Option<T>]> for Slots<T, SIZE> {
+    fn as_mut(&mut self) -> &mut [Option<T>] {
+        &mut self.slots
+    }
+}
+impl<T, const SIZE: usize> IntoIterator for Slots<T, SIZE> {
+    type Item = T;
+    type IntoIter = Flatten<IntoIter<Option<T>, SIZE>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.slots.into_iter().flatten()
+    }
+}
+impl<T, const SIZE: usize> Default for Slots<T, SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 /// A stack-allocated ring buffer
 #[derive(Debug)]
@@ -59,6 +261,8 @@ pub struct RingBuf<T, const SIZE: usize> {
     head: usize,
     /// The position of the next pending element
     tail: usize,
+    /// The amount of elements
+    len: usize,
 }
 impl<T, const SIZE: usize> RingBuf<T, SIZE> {
     /// The default value for non-copy const-time initialization
@@ -66,26 +270,290 @@ impl<T, const SIZE: usize> RingBuf<T, SIZE> {
 
     /// Creates a new empty ring buffer
     pub const fn new() -> Self {
-        Self { buf: [Self::INIT; SIZE], head: 0, tail: 0 }
+        Self { buf: [Self::INIT; SIZE], head: 0, tail: 0, len: 0 }
+    }
+
+    /// Pushes `element` into the ring buffer
+    pub const fn push(&mut self, element: T) -> Result<(), T> {
+        // Ensure that we have a free slot
+        if self.len == SIZE {
+            return Err(element);
+        }
+
+        // Insert the element into the buffer; the slot is empty, so forgetting the previous value is a no-op that
+        // allows a `const fn`
+        mem::forget(self.buf[self.head].replace(element));
+        self.head = (self.head + 1) % SIZE;
+        self.len += 1;
+        Ok(())
+    }
+    /// Pushes `element` to the front of the ring buffer, so that it is popped next
+    pub const fn push_front(&mut self, element: T) -> Result<(), T> {
+        // Ensure that we have a free slot
+        if self.len == SIZE {
+            return Err(element);
+        }
+
+        // Insert the element into the slot before the tail; the slot is empty, so forgetting the previous value is a
+        // no-op that allows a `const fn`
+        let tail = (self.tail + SIZE - 1) % SIZE;
+        mem::forget(self.buf[tail].replace(element));
+        self.tail = tail;
+        self.len += 1;
+        Ok(())
+    }
+    /// Pushes `element` into the ring buffer, evicting and returning the oldest element if the buffer is full
+    ///
+    /// A ring buffer with `SIZE == 0` cannot hold any element, so `element` itself is returned immediately.
+    pub fn push_overwrite(&mut self, element: T) -> Option<T> {
+        // A zero-capacity buffer has no slot to evict
+        if SIZE == 0 {
+            return Some(element);
+        }
+
+        // Evict the oldest element if the queue is full
+        let evicted = match self.is_full() {
+            true => self.pop(),
+            false => None,
+        };
+
+        // Insert the element into the now free slot
+        self.push(element).unwrap_or_else(|_| unreachable!("failed to push into free slot"));
+        evicted
+    }
+    /// Pops an element from the ring buffer
+    pub const fn pop(&mut self) -> Option<T> {
+        // Ensure that we have an element
+        if self.len == 0 {
+            return None;
+        }
+
+        // Take the element
+        let element = self.buf[self.tail].take();
+        self.tail = (self.tail + 1) % SIZE;
+        self.len -= 1;
+        element
+    }
+    /// Pops the most recently pushed element from the back of the ring buffer, i.e. in LIFO order
+    pub const fn pop_back(&mut self) -> Option<T> {
+        // Ensure that we have an element
+        if self.len == 0 {
+            return None;
+        }
+
+        // Take the element from the slot before the head
+        let head = (self.head + SIZE - 1) % SIZE;
+        let element = self.buf[head].take();
+        self.head = head;
+        self.len -= 1;
+        element
+    }
+    /// Pops the next pending element only if `predicate` returns `true` for it; otherwise the element stays in place
+    pub fn pop_if<F>(&mut self, predicate: F) -> Option<T>
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        match predicate(self.peek()?) {
+            true => self.pop(),
+            false => None,
+        }
+    }
+
+    /// Removes all elements for which `keep` returns `false`, and keeps the remaining elements in FIFO order
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        // Rotate the buffer once and re-push the retained elements
+        for _ in 0..self.len() {
+            let element = self.pop().expect("missing pending element");
+            if keep(&element) {
+                self.push(element).unwrap_or_else(|_| unreachable!("failed to re-push element"));
+            }
+        }
+    }
+
+    /// Gets a reference to the pending element at `index`, counted from the next pending element in FIFO order
+    pub const fn get(&self, index: usize) -> Option<&T> {
+        // Ensure that we have the element; this also avoids a division by zero for zero-capacity buffers
+        if index >= self.len {
+            return None;
+        }
+        self.buf[(self.tail + index) % SIZE].as_ref()
+    }
+    /// Gets a reference to the next pending element without removing it
+    pub const fn peek(&self) -> Option<&T> {
+        // Ensure that we have an element; this also avoids indexing into a zero-capacity buffer
+        if self.len == 0 {
+            return None;
+        }
+        self.buf[self.tail].as_ref()
+    }
+    /// Whether any pending element matches the given predicate
+    pub fn any<F>(&self, predicate: F) -> bool
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.iter().any(predicate)
+    }
+    /// The pending elements as up to two contiguous regions, similar to `VecDeque::as_slices`
+    ///
+    /// # Note on ordering
+    /// The first slice starts at the oldest pending element (the tail), the second slice contains the elements that
+    /// wrapped around to the start of the buffer; the concatenation of both slices is in FIFO order. Each returned slot
+    /// is occupied, so all elements are `Some`.
+    pub fn as_slices(&self) -> (&[Option<T>], &[Option<T>]) {
+        match self.is_empty() {
+            true => (&[], &[]),
+            false if self.tail < self.head => (&self.buf[self.tail..self.head], &[]),
+            false => (&self.buf[self.tail..], &self.buf[..self.head]),
+        }
+    }
+    /// Iterates over the pending elements in FIFO order without removing them
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.into_iter()
+    }
+    /// Iterates mutably over the pending elements in FIFO order without removing them
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        // The pending elements start at the tail and wrap around; free slots are empty and skipped
+        let (wrapped, unwrapped) = self.buf.split_at_mut(self.tail);
+        unwrapped.iter_mut().flatten().chain(wrapped.iter_mut().flatten())
+    }
+    /// Removes the pending elements in FIFO order and yields them
+    ///
+    /// Once the iterator is dropped, the ring buffer is empty; elements that have not been yielded are dropped, and the
+    /// buffer positions are reset to the start of the buffer.
+    pub fn drain(&mut self) -> Drain<'_, T, SIZE> {
+        Drain { ringbuf: self }
+    }
+
+    /// The amount of elements in the ring buffer
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether the ring buffer is empty
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Whether the ring buffer is full
+    pub const fn is_full(&self) -> bool {
+        self.len == SIZE
+    }
+    /// The maximum amount of elements
+    pub const fn capacity() -> usize {
+        SIZE
+    }
+}
+impl<'a, T, const SIZE: usize> IntoIterator for &'a RingBuf<T, SIZE> {
+    type Item = &'a T;
+    type IntoIter = Chain<Flatten<slice::Iter<'a, Option<T>>>, Flatten<slice::Iter<'a, Option<T>>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        // The pending elements start at the tail and wrap around; free slots are empty and skipped
+        let (wrapped, unwrapped) = self.buf.split_at(self.tail);
+        unwrapped.iter().flatten().chain(wrapped.iter().flatten())
+    }
+}
+#[cfg(feature = "defmt")]
+impl<T, const SIZE: usize> defmt::Format for RingBuf<T, SIZE> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "RingBuf {{ len: {=usize}, capacity: {=usize} }}", self.len(), SIZE);
+    }
+}
+impl<T, const SIZE: usize> Default for RingBuf<T, SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A draining iterator over the pending elements of a ring buffer, see [`RingBuf::drain`]
+#[derive(Debug)]
+pub struct Drain<'a, T, const SIZE: usize> {
+    /// The drained ring buffer
+    ringbuf: &'a mut RingBuf<T, SIZE>,
+}
+impl<T, const SIZE: usize> Iterator for Drain<'_, T, SIZE> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ringbuf.pop()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.ringbuf.len(), Some(self.ringbuf.len()))
+    }
+}
+impl<T, const SIZE: usize> ExactSizeIterator for Drain<'_, T, SIZE> {
+    // Marker trait, no members to implement
+}
+impl<T, const SIZE: usize> Drop for Drain<'_, T, SIZE> {
+    fn drop(&mut self) {
+        // Drop the remaining elements and reset the positions
+        while self.ringbuf.pop().is_some() {}
+        self.ringbuf.head = 0;
+        self.ringbuf.tail = 0;
+    }
+}
+
+/// A ring buffer that is backed by a caller-provided slice, so that the capacity is a runtime value
+#[derive(Debug)]
+pub struct SliceRingBuf<'a, T> {
+    /// The ring buffer
+    buf: &'a mut [Option<T>],
+    /// The position of the next free slot
+    head: usize,
+    /// The position of the next pending element
+    tail: usize,
+    /// The amount of elements
+    len: usize,
+}
+impl<'a, T> SliceRingBuf<'a, T> {
+    /// Creates a new empty ring buffer backed by `buf`; elements that are already in `buf` are dropped
+    pub fn new(buf: &'a mut [Option<T>]) -> Self {
+        buf.iter_mut().for_each(|slot| *slot = None);
+        Self { buf, head: 0, tail: 0, len: 0 }
     }
 
     /// Pushes `element` into the ring buffer
     pub fn push(&mut self, element: T) -> Result<(), T> {
-        // Check if the head-slot is empty, otherwise the queue is full
-        if self.buf[self.head].is_some() {
+        // Ensure that we have a free slot
+        if self.is_full() {
             return Err(element);
         }
 
         // Insert the element into the buffer
         self.buf[self.head] = Some(element);
-        self.head = (self.head + 1) % SIZE;
+        self.head = (self.head + 1) % self.buf.len();
+        self.len += 1;
         Ok(())
     }
     /// Pops an element from the ring buffer
     pub fn pop(&mut self) -> Option<T> {
+        // Ensure that we have an element
+        if self.len == 0 {
+            return None;
+        }
+
         // Take the element
-        let element = self.buf[self.tail].take()?;
-        self.tail = (self.tail + 1) % SIZE;
-        Some(element)
+        let element = self.buf[self.tail].take();
+        self.tail = (self.tail + 1) % self.buf.len();
+        self.len -= 1;
+        element
+    }
+
+    /// The amount of elements in the ring buffer
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether the ring buffer is empty
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Whether the ring buffer is full
+    pub const fn is_full(&self) -> bool {
+        self.len == self.buf.len()
+    }
+    /// The maximum amount of elements, i.e. the length of the backing slice
+    pub const fn capacity(&self) -> usize {
+        self.buf.len()
     }
 }
@@ -2,146 +2,2289 @@
 
 use crate::{
     boxes::{Box, CopyBox},
-    collections::{RingBuf, Stack},
-    runtime,
+    collections::{RingBuf, Slots, Stack},
+    recorder::{RecordEvent, Recorder},
+    runtime::{LinkedRuntime, Runtime},
     threadsafe::ThreadSafeCell,
 };
-use core::{any::TypeId, mem};
+use core::{
+    any::TypeId,
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+    future::Future,
+    marker::PhantomData,
+    mem,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+/// An opaque handle to a registered event listener
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId {
+    /// The slot index of the listener (or the index of the late observer) at registration time; the listener may be
+    /// moved by [`EventLoop::listen_front`], and late observers move if a preceding late observer is removed
+    pub(crate) index: usize,
+    /// The generation of the listener to detect reused slots
+    pub(crate) generation: usize,
+}
+
+/// An error that is returned if an event could not be sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendError<T> {
+    /// The backlog is reached
+    BacklogFull(T),
+    /// There is no listener for the event type
+    NoListener(T),
+}
+impl<T> SendError<T> {
+    /// Returns the event that could not be sent
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::BacklogFull(event) => event,
+            Self::NoListener(event) => event,
+        }
+    }
+}
+impl<T> Display for SendError<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::BacklogFull(_) => write!(f, "backlog is full"),
+            Self::NoListener(_) => write!(f, "no listener for event type"),
+        }
+    }
+}
+impl<T> Error for SendError<T> where T: Debug {}
+
+/// An error that is returned if a listener could not be registered; the callback can be recovered via
+/// [`Self::into_inner`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListenError<F> {
+    /// The callback is larger than the stackbox size of the event loop
+    TooLarge {
+        /// The callback
+        callback: F,
+        /// The stackbox size of the event loop
+        available: usize,
+    },
+    /// All listener slots are in use
+    Full {
+        /// The callback
+        callback: F,
+        /// The maximum amount of listeners of the event loop
+        capacity: usize,
+    },
+    /// There is already a listener for the event type, and the event loop only allows one listener per event type
+    DuplicateType {
+        /// The callback
+        callback: F,
+    },
+}
+impl<F> ListenError<F> {
+    /// Returns the callback that could not be registered
+    pub fn into_inner(self) -> F {
+        match self {
+            Self::TooLarge { callback, .. } => callback,
+            Self::Full { callback, .. } => callback,
+            Self::DuplicateType { callback } => callback,
+        }
+    }
+    /// Maps the callback via `f` and keeps the error reason
+    pub fn map<G, M>(self, f: M) -> ListenError<G>
+    where
+        M: FnOnce(F) -> G,
+    {
+        match self {
+            Self::TooLarge { callback, available } => ListenError::TooLarge { callback: f(callback), available },
+            Self::Full { callback, capacity } => ListenError::Full { callback: f(callback), capacity },
+            Self::DuplicateType { callback } => ListenError::DuplicateType { callback: f(callback) },
+        }
+    }
+}
+impl<F> Display for ListenError<F> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::TooLarge { available, .. } => write!(f, "callback does not fit into stackbox of {available} bytes"),
+            Self::Full { capacity, .. } => write!(f, "all {capacity} listener slots are in use"),
+            Self::DuplicateType { .. } => write!(f, "there is already a listener for the event type"),
+        }
+    }
+}
+impl<F> Error for ListenError<F> where F: Debug {}
+
+/// An error that is returned if a listener with the callback `F` could not be bootstrapped with the seed `S`; the seed
+/// can be recovered via [`Self::into_inner`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootstrapError<S, F> {
+    /// The listener could not be registered
+    Listen {
+        /// The reason, including the callback
+        error: ListenError<F>,
+        /// The seed
+        seed: S,
+    },
+    /// The backlog is reached; the listener has been removed again
+    BacklogFull {
+        /// The callback
+        callback: F,
+        /// The seed
+        seed: S,
+    },
+}
+impl<S, F> BootstrapError<S, F> {
+    /// Returns the seed that could not be sent
+    pub fn into_inner(self) -> S {
+        match self {
+            Self::Listen { seed, .. } => seed,
+            Self::BacklogFull { seed, .. } => seed,
+        }
+    }
+}
+impl<S, F> Display for BootstrapError<S, F> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Listen { error, .. } => write!(f, "failed to register listener: {error}"),
+            Self::BacklogFull { .. } => write!(f, "backlog is full"),
+        }
+    }
+}
+impl<S, F> Error for BootstrapError<S, F>
+where
+    S: Debug,
+    F: Debug,
+{
+}
+
+/// The policy that is applied if an event is sent to a full backlog
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// The event is rejected and returned as error
+    #[default]
+    Reject,
+    /// The oldest pending event of the same priority is dropped to make room for the event
+    DropOldest,
+    /// The event loop panics, e.g. to detect an undersized backlog during development
+    Panic,
+}
+
+/// The thread model of event loops that can be shared between threads, interrupt handlers or cores, so that all events
+/// and listener states must be `Send`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shared;
+/// The thread model of event loops that are confined to a single thread, so that events and listener states need not
+/// be `Send`; such event loops are neither `Send` nor `Sync`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Local(PhantomData<*const ()>);
 
-/// The size of a function pointer
-const FPTR_SIZE: usize = mem::size_of::<fn()>();
+/// A value that can be moved into an event loop with the thread model `M`, i.e. any `Send` value for [`Shared`] event
+/// loops and any value for [`Local`] event loops
+pub trait Sendable<M>: 'static {}
+impl<T> Sendable<Shared> for T where T: Send + 'static {}
+impl<T> Sendable<Local> for T where T: 'static {}
+
+/// A snapshot of the event loop metrics
+///
+/// # Note on portability
+/// The counters are plain integers that are only updated within the threadsafe context of the event loop. They do not
+/// rely on atomic read-modify-write operations, so the metrics also work on targets without compare-and-swap support
+/// (e.g. Cortex-M0/M0+ like the rp2040).
+#[cfg(feature = "metrics")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EventLoopMetrics {
+    /// The total amount of enqueued events
+    pub enqueued: usize,
+    /// The total amount of dispatched events
+    pub dispatched: usize,
+    /// The total amount of events that have been dropped due to a full backlog
+    pub dropped: usize,
+    /// The total amount of events that have not been consumed by any listener
+    pub unhandled: usize,
+}
+
+/// A type specific caller that invokes a boxed callback with a boxed event, and returns the remaining event and the
+/// callback
+type Caller<const SIZE: usize, const STATE_SIZE: usize> =
+    fn(Box<SIZE>, Box<STATE_SIZE>) -> (Option<Box<SIZE>>, Box<STATE_SIZE>);
+/// A predicate together with the callback of a filtered listener
+type FilteredCallback<T> = (fn(&T) -> bool, fn(T) -> Option<T>);
+/// A plain function callback for events of type `T`
+pub(crate) type FnCallback<T> = fn(T) -> Option<T>;
+/// A function to insert a pending event into a backlog
+type PushFn<const SIZE: usize, const BACKLOG_MAX: usize> =
+    fn(&mut RingBuf<PendingEvent<SIZE>, BACKLOG_MAX>, PendingEvent<SIZE>) -> Result<(), PendingEvent<SIZE>>;
 
 /// An event listener with the associated type and a type-specific caller implementation
+///
+/// Listeners are opaque; they are only public to allow caller-provided listener storage, see
+/// [`SliceEventLoop`](crate::sliced::SliceEventLoop).
+#[derive(Debug)]
+pub struct EventListener<const SIZE: usize, const STATE_SIZE: usize = SIZE> {
+    /// The type ID
+    pub(crate) type_id: TypeId,
+    /// The generation of the listener
+    pub(crate) generation: usize,
+    /// Whether the listener is removed after its first invocation
+    pub(crate) once: bool,
+    /// Whether the listener only observes the event and is invoked before the consuming listeners
+    pub(crate) observer: bool,
+    /// The boxed callback, or `None` if the callback is currently being invoked
+    pub(crate) callback_box: Option<Box<STATE_SIZE>>,
+    /// A type specific caller to invoke the callback
+    pub(crate) caller: Caller<SIZE, STATE_SIZE>,
+}
+
+/// A late observer that is invoked with a reference to an event after the listener chain
 #[derive(Debug, Clone, Copy)]
-struct EventListener<const SIZE: usize> {
+struct AfterListener<const SIZE: usize> {
     /// The type ID
     pub type_id: TypeId,
-    /// The boxed callback
-    pub callback_box: CopyBox<FPTR_SIZE>,
-    /// A type specific caller to invoke the callback
-    pub caller: fn(Box<SIZE>, CopyBox<FPTR_SIZE>) -> Option<Box<SIZE>>,
+    /// The generation of the late observer, which is shared with the listener generations
+    pub generation: usize,
+    /// The observer callback
+    pub callback: CopyBox<FPTR_SIZE>,
+    /// A type specific caller to invoke the callback with a reference to the boxed event
+    pub caller: fn(&Box<SIZE>, &CopyBox<FPTR_SIZE>),
+    /// A type specific function to snapshot the boxed event before it is passed to the listener chain
+    pub snapshot: fn(&Box<SIZE>) -> Box<SIZE>,
+}
+
+/// A pending event in the backlog
+#[derive(Debug)]
+struct PendingEvent<const SIZE: usize> {
+    /// The boxed event
+    pub event_box: Box<SIZE>,
+    /// Whether the event is passed to every listener instead of along the listener chain
+    pub broadcast: bool,
+}
+impl<const SIZE: usize> PendingEvent<SIZE> {
+    /// Creates a new pending event that is passed along the listener chain
+    const fn new(event_box: Box<SIZE>) -> Self {
+        Self { event_box, broadcast: false }
+    }
+}
+
+impl<const SIZE: usize, const STATE_SIZE: usize> EventListener<SIZE, STATE_SIZE> {
+    /// Clones the listener, returns `None` if the callback is not cloneable or is currently being invoked
+    fn try_clone(&self) -> Option<Self> {
+        let callback_box = self.callback_box.as_ref()?.try_clone()?;
+        let (type_id, generation, once, observer, caller) =
+            (self.type_id, self.generation, self.once, self.observer, self.caller);
+        Some(Self { type_id, generation, once, observer, callback_box: Some(callback_box), caller })
+    }
+}
+
+/// A snapshot of the listeners of an event loop, see [`EventLoop::snapshot_listeners`]
+#[derive(Debug)]
+pub struct ListenerSnapshot<
+    const STACKBOX_SIZE: usize = 64,
+    const LISTENERS_MAX: usize = 32,
+    const STATE_SIZE: usize = STACKBOX_SIZE,
+> {
+    /// The captured listeners in their original slots
+    listeners: Slots<EventListener<STACKBOX_SIZE, STATE_SIZE>, LISTENERS_MAX>,
+    /// The late observers
+    after: Stack<AfterListener<STACKBOX_SIZE>, LISTENERS_MAX>,
+}
+impl<const STACKBOX_SIZE: usize, const LISTENERS_MAX: usize, const STATE_SIZE: usize>
+    ListenerSnapshot<STACKBOX_SIZE, LISTENERS_MAX, STATE_SIZE>
+{
+    /// Captures all listeners with cloneable callbacks, and the late observers
+    fn capture(
+        listeners: &Slots<EventListener<STACKBOX_SIZE, STATE_SIZE>, LISTENERS_MAX>,
+        after: Stack<AfterListener<STACKBOX_SIZE>, LISTENERS_MAX>,
+    ) -> Self {
+        let mut captured = Slots::new();
+        for index in 0..LISTENERS_MAX {
+            if let Some(listener) = listeners.get(index).and_then(EventListener::try_clone) {
+                captured.insert_at(index, listener).unwrap_or_else(|_| unreachable!("failed to insert into free slot"));
+            }
+        }
+        Self { listeners: captured, after }
+    }
+
+    /// The amount of captured listeners, observers and late observers
+    pub const fn len(&self) -> usize {
+        self.listeners.len() + self.after.len()
+    }
+    /// Whether no listeners, observers or late observers have been captured
+    pub const fn is_empty(&self) -> bool {
+        self.listeners.is_empty() && self.after.is_empty()
+    }
+}
+impl<const STACKBOX_SIZE: usize, const LISTENERS_MAX: usize, const STATE_SIZE: usize> Clone
+    for ListenerSnapshot<STACKBOX_SIZE, LISTENERS_MAX, STATE_SIZE>
+{
+    fn clone(&self) -> Self {
+        Self::capture(&self.listeners, self.after)
+    }
+}
+
+/// The listener invocation that is currently in progress, for diagnostic purposes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Breadcrumb {
+    /// The type name of the event, or `"<unknown>"` if the `type-name` feature is disabled
+    pub event: &'static str,
+    /// The type name of the listener callback, or `"<unknown>"` if the `type-name` feature is disabled
+    pub listener: &'static str,
+}
+
+/// The listener invocation that is currently in progress
+///
+/// The breadcrumb is shared by all event loops and therefore always uses the [`LinkedRuntime`].
+#[cfg(feature = "breadcrumb")]
+static BREADCRUMB: ThreadSafeCell<Option<Breadcrumb>> = ThreadSafeCell::new(None);
+
+/// Gets the listener invocation that is currently in progress, if any
+///
+/// This is intended to be called from a custom panic handler to log which listener panicked for which event. Note that
+/// the breadcrumb is not reset if a listener panics and the panic unwinds.
+#[cfg(feature = "breadcrumb")]
+pub fn current_breadcrumb() -> Option<Breadcrumb> {
+    // Don't panic if the panic handler is invoked while the breadcrumb is borrowed
+    BREADCRUMB.try_scope(|breadcrumb| *breadcrumb).flatten()
+}
+
+/// Finds the slot of the listener with the given generation, starting with the slot `hint` where it was last seen
+pub(crate) fn find_listener<const SIZE: usize, const STATE_SIZE: usize>(
+    listeners: &[Option<EventListener<SIZE, STATE_SIZE>>],
+    generation: usize,
+    hint: usize,
+) -> Option<usize> {
+    let is_listener = |index: &usize| {
+        let listener = listeners.get(*index).and_then(Option::as_ref);
+        listener.is_some_and(|listener| listener.generation == generation)
+    };
+    Some(hint).filter(is_listener).or_else(|| (0..listeners.len()).find(is_listener))
+}
+/// Passes an event along the listeners that match its type and are observers if `observers` is set, and returns the
+/// remaining event (or `None` if a listener consumed it) together with whether the event has been broadcast
+///
+/// Broadcast events are passed as clone to every matching consuming listener, and the values returned by the listeners
+/// are ignored. The listeners are checked out one by one, so that the callbacks are invoked outside of the threadsafe
+/// scope and may access the listeners.
+pub(crate) fn dispatch_chain<L, R, const SIZE: usize, const STATE_SIZE: usize>(
+    listeners: &ThreadSafeCell<L, R>,
+    mut event_box: Box<SIZE>,
+    observers: bool,
+    broadcast: bool,
+) -> (Option<Box<SIZE>>, bool)
+where
+    L: AsMut<[Option<EventListener<SIZE, STATE_SIZE>>]>,
+    R: Runtime,
+{
+    let (type_id, mut start, mut broadcasted) = (event_box.inner_type_id(), 0, false);
+    loop {
+        // Check out the callback of the next listener that matches the event type within a single scope
+        let checkout = listeners.scope(|listeners| {
+            listeners.as_mut().iter_mut().enumerate().skip(start).find_map(|(index, slot)| {
+                // Listeners that are being invoked by an outer dispatch have no callback and are skipped
+                let listener =
+                    slot.as_mut().filter(|listener| listener.type_id == type_id && listener.observer == observers)?;
+                let callback_box = listener.callback_box.take()?;
+                Some((index, listener.generation, listener.caller, callback_box))
+            })
+        });
+        let Some((index, generation, caller, callback_box)) = checkout else {
+            // There are no more listeners that can process the box
+            return (Some(event_box), broadcasted);
+        };
+        // Leave a breadcrumb for panic handlers
+        #[cfg(feature = "breadcrumb")]
+        let previous_breadcrumb =
+            BREADCRUMB.replace(Some(Breadcrumb { event: event_box.type_name(), listener: callback_box.type_name() }));
+
+        // Call the callback and return it to the listener, unless the listener is gone or should fire only once
+        let (maybe_event_box, callback_box) = match broadcast && !observers {
+            true => {
+                // Pass a clone and ignore the returned event
+                let event_box_clone = event_box.try_clone().expect("failed to clone broadcast event");
+                let (_ignored_event_box, callback_box) = caller(event_box_clone, callback_box);
+                broadcasted = true;
+                (Some(event_box), callback_box)
+            }
+            false => caller(event_box, callback_box),
+        };
+        #[cfg(feature = "breadcrumb")]
+        BREADCRUMB.replace(previous_breadcrumb);
+        // The listener may have been moved by a front insertion, so the chain continues after its current slot
+        let (moved_index, _removed_callback_box) = listeners.scope(|listeners| {
+            let listeners = listeners.as_mut();
+            let Some(index) = find_listener(listeners, generation, index) else {
+                return (index, Some(callback_box));
+            };
+            let listener = listeners[index].as_mut().expect("missing listener");
+            match listener.once {
+                true => {
+                    listeners[index] = None;
+                    (index, Some(callback_box))
+                }
+                false => (index, listener.callback_box.replace(callback_box)),
+            }
+        });
+        start = moved_index + 1;
+
+        // Continue with the returned event box
+        let Some(next_event_box) = maybe_event_box else {
+            return (None, broadcasted);
+        };
+        event_box = next_event_box;
+    }
+}
+
+/// Calls a plain function callback with an event
+///
+/// Function pointers are stateless, so the callback is borrowed instead of being unwrapped and re-boxed; this
+/// avoids generating the boxing code for the callback type per event type.
+pub(crate) fn fn_caller<T, const SIZE: usize, const STATE_SIZE: usize>(
+    boxed_event: Box<SIZE>,
+    boxed_callback: Box<STATE_SIZE>,
+) -> (Option<Box<SIZE>>, Box<STATE_SIZE>)
+where
+    T: 'static,
+{
+    // Recover the original types and call the callback
+    let event: T = boxed_event.into_inner().expect("failed to unwrap event");
+    let &callback: &fn(T) -> Option<T> = boxed_callback.as_ref().expect("failed to borrow callback");
+    let maybe_event = callback(event);
+
+    // Box the result
+    let boxed_event =
+        maybe_event.map(|event| Box::new(event).unwrap_or_else(|_| unreachable!("failed to re-box event")));
+    (boxed_event, boxed_callback)
+}
+
+/// Calls a callback with an event
+pub(crate) fn caller<T, F, const SIZE: usize, const STATE_SIZE: usize>(
+    boxed_event: Box<SIZE>,
+    boxed_callback: Box<STATE_SIZE>,
+) -> (Option<Box<SIZE>>, Box<STATE_SIZE>)
+where
+    T: 'static,
+    F: FnMut(T) -> Option<T> + 'static,
+{
+    // Recover the original types
+    let event: T = boxed_event.into_inner().expect("failed to unwrap event");
+    let mut callback: F = boxed_callback.into_inner().expect("failed to unwrap callback");
+
+    // Call the callback and box the result and the callback state
+    let maybe_event = callback(event);
+    let boxed_event =
+        maybe_event.map(|event| Box::new(event).unwrap_or_else(|_| unreachable!("failed to re-box event")));
+    let boxed_callback = Box::new(callback).unwrap_or_else(|_| unreachable!("failed to re-box callback"));
+    (boxed_event, boxed_callback)
+}
+
+/// A type-erased event loop that can send events of type `T`
+trait SendEvent<T> {
+    /// Sends an event to the event loop, returns `Err(event)` if the backlog is reached
+    fn send_event(&self, event: T) -> Result<(), T>;
+}
+
+/// A lightweight handle to send events of type `T` to an event loop, without depending on the event loop capacities
+///
+/// A `Sender<'static, T>` can be created from a `&'static EventLoop`, and can be stored e.g. in a driver struct or be
+/// used from an interrupt handler.
+pub struct Sender<'a, T> {
+    /// The event loop
+    eventloop: &'a (dyn SendEvent<T> + Sync),
+}
+impl<T> Sender<'_, T> {
+    /// Sends an event to the event loop, returns `Err(event)` if the backlog is reached
+    pub fn send(&self, event: T) -> Result<(), T> {
+        self.eventloop.send_event(event)
+    }
+}
+impl<T> Clone for Sender<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for Sender<'_, T> {
+    // Marker trait, no members to implement
+}
+impl<T> Debug for Sender<'_, T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+/// A type-erased event loop that can commit events of type `T` into reserved backlog slots
+trait ReserveEvent<T> {
+    /// Sends an event to the event loop using a reserved backlog slot
+    fn commit_reserved(&self, event: T);
+    /// Releases a reserved backlog slot without using it
+    fn release_reserved(&self);
+}
+
+/// A reserved slot in the lowest-priority backlog of an event loop, which allows to send an event of type `T`
+/// infallibly, e.g. from an interrupt handler
+///
+/// The slot is returned to the event loop if the reservation is dropped without being committed.
+pub struct Reservation<'a, T> {
+    /// The event loop
+    eventloop: &'a (dyn ReserveEvent<T> + Sync),
+}
+impl<T> Reservation<'_, T> {
+    /// Sends an event to the event loop using the reserved slot
+    pub fn commit(self, event: T) {
+        self.eventloop.commit_reserved(event);
+        mem::forget(self);
+    }
+}
+impl<T> Drop for Reservation<'_, T> {
+    fn drop(&mut self) {
+        self.eventloop.release_reserved();
+    }
+}
+impl<T> Debug for Reservation<'_, T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Reservation").finish_non_exhaustive()
+    }
+}
+
+/// The listener state of an awaited event of type `T`
+struct AwaitState<T> {
+    /// The received event, if any
+    event: Option<T>,
+    /// The waker of the task that awaits the event, if any
+    waker: Option<Waker>,
+}
+
+/// A type-erased event loop that can be polled for the next event of type `T`
+trait AwaitEvent<T> {
+    /// Takes the received event of the listener `id`, or registers `waker` to be woken once the event is received;
+    /// resolves to `None` if the listener has been removed
+    fn poll_event(&self, id: ListenerId, waker: &Waker) -> Poll<Option<T>>;
+    /// Removes the listener `id` if it still exists
+    fn cancel_event(&self, id: ListenerId);
+}
+
+/// A future that resolves to the next event of type `T` that is dispatched by an event loop
+///
+/// The future is backed by a listener that is removed again once the future resolves or is dropped. If the listener is
+/// removed by other means before an event has been received (e.g. via [`EventLoop::unlisten`],
+/// [`EventLoop::clear_listeners`] or [`EventLoop::restore_listeners`]), the future resolves to `None`.
+pub struct NextEvent<'a, T> {
+    /// The event loop
+    eventloop: &'a (dyn AwaitEvent<T> + Sync),
+    /// The listener that receives the event
+    id: ListenerId,
+}
+impl<T> Future for NextEvent<'_, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.eventloop.poll_event(self.id, cx.waker())
+    }
+}
+impl<T> Drop for NextEvent<'_, T> {
+    fn drop(&mut self) {
+        self.eventloop.cancel_event(self.id);
+    }
+}
+impl<T> Debug for NextEvent<'_, T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("NextEvent").field("id", &self.id).finish_non_exhaustive()
+    }
 }
 
+/// The size of a function pointer, which is the minimum size of a stack box that can hold a plain listener
+pub const FPTR_SIZE: usize = mem::size_of::<fn()>();
+
 /// An event loop
+///
+/// # Note on `STACKBOX_SIZE` and `STATE_SIZE`
+/// Events are stored in stack boxes of `STACKBOX_SIZE` bytes, and listener callbacks are stored in separate stack
+/// boxes of `STATE_SIZE` bytes, which defaults to `STACKBOX_SIZE`. This allows e.g. large events without oversizing the
+/// listener slots, and vice versa. `STATE_SIZE` must be at least [`FPTR_SIZE`] bytes large to hold a plain function
+/// pointer listener; this is checked at compile time. Since stateful closures also store their captures, a size of at
+/// least `4 * FPTR_SIZE` is recommended.
+///
+/// # Note on capacities
+/// `BACKLOG_MAX`, `LISTENERS_MAX` and `PRIORITIES` must not be zero, and `STATE_SIZE` must be able to hold a function
+/// pointer. These constraints are checked at compile time when an event loop is created, so an invalid configuration
+/// fails the build instead of panicking at runtime.
+///
+/// # Note on priorities
+/// The event loop has `PRIORITIES` separate backlogs with `BACKLOG_MAX` slots each, where a higher index means a
+/// higher priority. Pending events of the highest non-empty priority are always processed first; events of the same
+/// priority are processed in order. The plain `send*` methods use the lowest priority `0`.
+///
+/// # Note on runtimes
+/// The runtime `R` provides the platform-specific primitives to wait for and raise events, and to run code within a
+/// critical section. The default [`LinkedRuntime`] forwards to the function stubs of the linked runtime crate; a custom
+/// runtime can be used e.g. to mock the platform in tests.
+///
+/// # Note on `Send`
+/// Events and listener states are type-erased once they are boxed, so the event loop itself is always `Sync`, and they
+/// may be taken out of the event loop in a different thread, interrupt handler or core than the one they have been
+/// sent from. Therefore, the thread model `M` defaults to [`Shared`], which requires all events and listener states to
+/// be `Send` (see [`Sendable`]). For explicitly single-threaded use, [`LocalEventLoop`] accepts values that are not
+/// `Send` (e.g. `Rc`), but is neither `Send` nor `Sync` itself and does not provide the `Sync` handles (e.g.
+/// [`Self::sender`]).
 #[derive(Debug)]
-pub struct EventLoop<const STACKBOX_SIZE: usize = 64, const BACKLOG_MAX: usize = 32, const LISTENERS_MAX: usize = 32> {
-    /// The event buffer
-    events: ThreadSafeCell<RingBuf<Box<STACKBOX_SIZE>, BACKLOG_MAX>>,
+pub struct EventLoop<
+    const STACKBOX_SIZE: usize = 64,
+    const BACKLOG_MAX: usize = 32,
+    const LISTENERS_MAX: usize = 32,
+    const PRIORITIES: usize = 1,
+    R: Runtime = LinkedRuntime,
+    const STATE_SIZE: usize = STACKBOX_SIZE,
+    M = Shared,
+> {
+    /// The event buffers, one per priority
+    events: ThreadSafeCell<[RingBuf<PendingEvent<STACKBOX_SIZE>, BACKLOG_MAX>; PRIORITIES], R>,
+    /// The deferred events that are merged into the lowest-priority backlog once the backlog has been drained
+    deferred: ThreadSafeCell<RingBuf<PendingEvent<STACKBOX_SIZE>, BACKLOG_MAX>, R>,
     /// The event listeners
-    listeners: ThreadSafeCell<Stack<EventListener<STACKBOX_SIZE>, LISTENERS_MAX>>,
+    listeners: ThreadSafeCell<Slots<EventListener<STACKBOX_SIZE, STATE_SIZE>, LISTENERS_MAX>, R>,
+    /// The late observers that are invoked after the listener chain
+    after: ThreadSafeCell<Stack<AfterListener<STACKBOX_SIZE>, LISTENERS_MAX>, R>,
+    /// The generation counter for new listeners
+    generation: ThreadSafeCell<usize, R>,
+    /// The hook for events that have not been consumed by any listener
+    unhandled_hook: ThreadSafeCell<Option<fn(&Box<STACKBOX_SIZE>)>, R>,
+    /// The recorder for dispatched events
+    recorder: ThreadSafeCell<Option<&'static (dyn RecordEvent<STACKBOX_SIZE> + Sync)>, R>,
+    /// The amount of reserved slots in the lowest-priority backlog
+    reserved: ThreadSafeCell<usize, R>,
+    /// Whether at most one listener per event type may be registered
+    unique_listeners: bool,
+    /// The policy if an event is sent to a full backlog
+    policy: OverflowPolicy,
+    /// The maximum amount of events that are processed in a row before the idle hook is called
+    dispatch_budget: usize,
+    /// The event loop metrics
+    #[cfg(feature = "metrics")]
+    metrics: ThreadSafeCell<EventLoopMetrics, R>,
+    /// The thread model
+    thread_model: PhantomData<M>,
 }
-impl<const STACKBOX_SIZE: usize, const BACKLOG_MAX: usize, const LISTENERS_MAX: usize>
-    EventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX>
+/// An event loop that is confined to a single thread, so that events and listener states need not be `Send`
+///
+/// See the note on `Send` of [`EventLoop`]; since this event loop is not `Sync`, it cannot be shared with interrupt
+/// handlers or other threads, e.g. via a `static`.
+pub type LocalEventLoop<
+    const STACKBOX_SIZE: usize = 64,
+    const BACKLOG_MAX: usize = 32,
+    const LISTENERS_MAX: usize = 32,
+    const PRIORITIES: usize = 1,
+    R = LinkedRuntime,
+    const STATE_SIZE: usize = STACKBOX_SIZE,
+> = EventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX, PRIORITIES, R, STATE_SIZE, Local>;
+impl<
+        const STACKBOX_SIZE: usize,
+        const BACKLOG_MAX: usize,
+        const LISTENERS_MAX: usize,
+        const PRIORITIES: usize,
+        R,
+        const STATE_SIZE: usize,
+        M,
+    > EventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX, PRIORITIES, R, STATE_SIZE, M>
+where
+    R: Runtime,
 {
+    /// An empty event buffer
+    const EVENTS_INIT: RingBuf<PendingEvent<STACKBOX_SIZE>, BACKLOG_MAX> = RingBuf::new();
+
     /// Creates a new event loop
     pub const fn new() -> Self {
-        let events = ThreadSafeCell::new(RingBuf::new());
-        let listeners = ThreadSafeCell::new(Stack::new());
-        Self { events, listeners }
+        Self::new_impl(false, OverflowPolicy::Reject)
+    }
+    /// Creates a new event loop that allows at most one listener or observer per event type
+    ///
+    /// Adding another listener for an event type that already has a listener fails and returns the callback. This is
+    /// useful to detect accidental duplicate registrations, e.g. if an initialization routine is called twice.
+    pub const fn new_unique() -> Self {
+        Self::new_impl(true, OverflowPolicy::Reject)
+    }
+    /// Creates a new event loop with the given policy if an event is sent to a full backlog
+    ///
+    /// # Note on overflows
+    /// The policy applies to [`Self::send`], [`Self::try_send`], [`Self::send_prio`], [`Self::send_broadcast`] and
+    /// [`Self::send_front`]; the other `send*` methods keep their documented semantics. Since [`Self::send_front`] also
+    /// inserts at the front, [`OverflowPolicy::DropOldest`] makes it replace the event that would otherwise be
+    /// processed next. Reserved slots are never evicted; if all slots are reserved, the event is rejected (or the event
+    /// loop panics).
+    pub const fn with_policy(policy: OverflowPolicy) -> Self {
+        Self::new_impl(false, policy)
+    }
+    /// Limits the amount of events that [`Self::enter_with_idle`] processes in a row before it calls the idle hook to
+    /// `budget`
+    ///
+    /// This bounds the latency of the idle hook under load: once the budget is exhausted, the hook is called even if
+    /// the backlog is not empty, and processing continues afterwards. The budget is unbounded by default.
+    ///
+    /// # Panics
+    /// This function panics if `budget` is zero.
+    pub const fn with_dispatch_budget(mut self, budget: usize) -> Self {
+        assert!(budget > 0, "dispatch budget must not be zero");
+        self.dispatch_budget = budget;
+        self
+    }
+    /// Creates a new event loop with the given listener and overflow policies
+    const fn new_impl(unique_listeners: bool, policy: OverflowPolicy) -> Self {
+        // Validate the capacities at compile time
+        const { assert!(BACKLOG_MAX > 0, "BACKLOG_MAX must not be zero") };
+        const { assert!(LISTENERS_MAX > 0, "LISTENERS_MAX must not be zero") };
+        const { assert!(PRIORITIES > 0, "PRIORITIES must not be zero") };
+        const { assert!(STATE_SIZE >= FPTR_SIZE, "STATE_SIZE is too small to hold a function pointer") };
+        let events = ThreadSafeCell::with_runtime([Self::EVENTS_INIT; PRIORITIES]);
+        let deferred = ThreadSafeCell::with_runtime(RingBuf::new());
+        let listeners = ThreadSafeCell::with_runtime(Slots::new());
+        let after = ThreadSafeCell::with_runtime(Stack::new());
+        let generation = ThreadSafeCell::with_runtime(0);
+        let unhandled_hook = ThreadSafeCell::with_runtime(None);
+        let recorder = ThreadSafeCell::with_runtime(None);
+        let reserved = ThreadSafeCell::with_runtime(0);
+        Self {
+            events,
+            deferred,
+            listeners,
+            after,
+            generation,
+            unhandled_hook,
+            recorder,
+            reserved,
+            unique_listeners,
+            policy,
+            dispatch_budget: usize::MAX,
+            #[cfg(feature = "metrics")]
+            metrics: ThreadSafeCell::with_runtime(EventLoopMetrics {
+                enqueued: 0,
+                dispatched: 0,
+                dropped: 0,
+                unhandled: 0,
+            }),
+            thread_model: PhantomData,
+        }
     }
 
-    /// Adds a listener to the event loop which receives all events of type `T`
+    /// Adds a listener to the event loop which receives all events of type `T`, and returns a handle to remove the
+    /// listener again
     ///
     /// # Note on multiple listeners
     /// It is possible to chain multiple listeners for the same event type `T`. If the first invoked listener returns
     /// `Some(event)` again, the next listener is invoked with `event`, and so on. If at some point a listener returns
     /// `None`, the chain ends and subsequent listeners are not invoked anymore.
-    pub fn register<T>(&self, callback: fn(T) -> Option<T>) -> Result<(), fn(T) -> Option<T>>
+    pub fn listen<T>(&self, callback: fn(T) -> Option<T>) -> Result<ListenerId, ListenError<FnCallback<T>>>
     where
-        T: 'static,
+        T: Sendable<M>,
+    {
+        self.insert_listener::<T, _>(
+            callback,
+            Box::new_cloneable,
+            fn_caller::<T, STACKBOX_SIZE, STATE_SIZE>,
+            false,
+            false,
+            false,
+        )
+    }
+    /// Adds a stateful listener to the event loop which receives all events of type `T`, and returns a handle to remove
+    /// the listener again; returns [`ListenError::TooLarge`] if the closure is larger than `STATE_SIZE`
+    ///
+    /// # Note on multiple listeners
+    /// It is possible to chain multiple listeners for the same event type `T`. If the first invoked listener returns
+    /// `Some(event)` again, the next listener is invoked with `event`, and so on. If at some point a listener returns
+    /// `None`, the chain ends and subsequent listeners are not invoked anymore.
+    pub fn listen_fn<T, F>(&self, callback: F) -> Result<ListenerId, ListenError<F>>
+    where
+        T: Sendable<M>,
+        F: FnMut(T) -> Option<T> + Sendable<M>,
+    {
+        self.insert_listener::<T, F>(callback, Box::new, caller::<T, F, STACKBOX_SIZE, STATE_SIZE>, false, false, false)
+    }
+    /// Adds a listener to the event loop which receives all events of type `T` before all previously added listeners
+    /// for `T`, and returns a handle to remove the listener again
+    ///
+    /// # Note on multiple listeners
+    /// The listener becomes the head of the chain for `T`, so it gets the first chance to consume an event: if it
+    /// returns `None`, the chain ends and none of the previously added listeners is invoked for this event. This allows
+    /// to install e.g. a high-priority filter in front of existing handlers. Observers are not affected, since they are
+    /// always invoked before the chain.
+    ///
+    /// # Note on listener slots
+    /// To make room at the front, previously added listeners for `T` may be moved to other slots; their handles stay
+    /// valid. If the listener is added while an event of type `T` is being dispatched, it is invoked starting with the
+    /// next event.
+    pub fn listen_front<T>(&self, callback: fn(T) -> Option<T>) -> Result<ListenerId, ListenError<FnCallback<T>>>
+    where
+        T: Sendable<M>,
+    {
+        self.insert_listener::<T, _>(
+            callback,
+            Box::new_cloneable,
+            fn_caller::<T, STACKBOX_SIZE, STATE_SIZE>,
+            false,
+            false,
+            true,
+        )
+    }
+    /// Adds a listener to the event loop which receives the next event of type `T` only, and returns a handle to remove
+    /// the listener again before it fires
+    ///
+    /// # Note on multiple listeners
+    /// The listener is removed as soon as it has been invoked for the first matching event, regardless of its return
+    /// value. If it returns `Some(event)`, the chain continues with the next listener as usual; if it returns `None`,
+    /// the chain ends. A listener that is skipped because a previous listener ended the chain stays registered.
+    pub fn listen_once<T>(&self, callback: fn(T) -> Option<T>) -> Result<ListenerId, ListenError<FnCallback<T>>>
+    where
+        T: Sendable<M>,
     {
-        // Create the caller
-        let callback_box = CopyBox::new(callback).expect("cannot box function pointer");
-        let caller: fn(Box<STACKBOX_SIZE>, CopyBox<FPTR_SIZE>) -> Option<Box<STACKBOX_SIZE>> = Self::caller::<T>;
-        let listener = EventListener { type_id: TypeId::of::<T>(), callback_box, caller };
+        self.insert_listener::<T, _>(
+            callback,
+            Box::new_cloneable,
+            fn_caller::<T, STACKBOX_SIZE, STATE_SIZE>,
+            true,
+            false,
+            false,
+        )
+    }
+    /// Adds a listener to the event loop which receives all events of type `T` that match `predicate`, and returns a
+    /// handle to remove the listener again
+    ///
+    /// # Note on rejected events
+    /// The predicate is called with a reference to the event. If it returns `false`, the listener is skipped and the
+    /// untouched event is passed on to the next listener in the chain, as if the listener had returned `Some(event)`.
+    pub fn listen_where<T>(
+        &self,
+        predicate: fn(&T) -> bool,
+        callback: fn(T) -> Option<T>,
+    ) -> Result<ListenerId, ListenError<FnCallback<T>>>
+    where
+        T: Sendable<M>,
+    {
+        let callback = (predicate, callback);
+        let result =
+            self.insert_listener::<T, _>(callback, Box::new_cloneable, Self::filter_caller::<T>, false, false, false);
+        result.map_err(|error| error.map(|(_, callback)| callback))
+    }
+    /// Adds an observer to the event loop which inspects all events of type `T` without consuming them, and returns a
+    /// handle to remove the observer again
+    ///
+    /// # Note on dispatch order
+    /// All observers for an event are invoked before the consuming listener chain, regardless of the order in which
+    /// observers and listeners have been added. Since observers never consume the event, they cannot end the chain.
+    pub fn observe<T>(&self, callback: fn(&T)) -> Result<ListenerId, ListenError<fn(&T)>>
+    where
+        T: Sendable<M>,
+    {
+        self.insert_listener::<T, _>(callback, Box::new_cloneable, Self::observer_caller::<T>, false, true, false)
+    }
+    /// Adds a late observer to the event loop which inspects all events of type `T` after the listener chain, and
+    /// returns a handle to remove the late observer again via [`Self::unlisten`]
+    ///
+    /// # Note on dispatch order
+    /// Late observers are invoked in registration order after the observers and the listener chain, regardless of
+    /// whether a listener consumed the event (i.e. returned `None`). To achieve this, the event is cloned before it is
+    /// passed to the listener chain if there is at least one late observer for its type, and the late observers are
+    /// invoked with a reference to this snapshot. Late observers are stored separately from the listeners, with up to
+    /// `LISTENERS_MAX` late observers; for [`Self::new_unique`] event loops, they count as listeners of their type.
+    pub fn listen_after<T>(&self, callback: fn(&T)) -> Result<ListenerId, ListenError<fn(&T)>>
+    where
+        T: Clone + Sendable<M>,
+    {
+        // A function pointer always fits into the box, since the box has exactly the size of a function pointer
+        let callback_box = CopyBox::new(callback).expect("failed to box function pointer");
+        let (type_id, generation) = (TypeId::of::<T>(), self.next_generation());
+        let listener = AfterListener {
+            type_id,
+            generation,
+            callback: callback_box,
+            caller: Self::after_caller::<T>,
+            snapshot: Self::snapshot_event::<T>,
+        };
 
-        // Insert the listener
-        if self.listeners.scope(|listeners| listeners.push(listener)).is_err() {
-            return Err(callback);
-        }
-        Ok(())
+        // Insert the late observer unless there is already a listener for the type and the listeners must be unique
+        let inserted = self.listeners.scope(|listeners| {
+            let is_duplicate = self.unique_listeners && self.has_any_listener(listeners, type_id);
+            match is_duplicate {
+                true => Err(ListenError::DuplicateType { callback }),
+                false => self.after.scope(|after| match after.push(listener) {
+                    Ok(_) => Ok(after.len() - 1),
+                    Err(_) => Err(ListenError::Full { callback, capacity: LISTENERS_MAX }),
+                }),
+            }
+        });
+        let index = inserted?;
+        Ok(ListenerId { index, generation })
+    }
+    /// Creates a future that resolves to the next event of type `T`, e.g. to await an event from within an async task
+    ///
+    /// The event is received by a listener that is registered immediately, so events that are dispatched before the
+    /// future is polled for the first time are not missed. The listener consumes the event, unless it has already
+    /// received an event that has not been awaited yet; it is removed once the future resolves or is dropped. The
+    /// future resolves to `None` if the listener is removed before it has received an event, e.g. via
+    /// [`Self::clear_listeners`]. Returns an error if the listener state (the event and a [`Waker`]) does not fit into
+    /// `STATE_SIZE`, or if the listener cannot be registered.
+    ///
+    /// # Note on executors
+    /// The event is only received if the event loop is running (e.g. via [`Self::run_until_idle`] or [`Self::enter`]);
+    /// the future is woken via its waker once the event is received.
+    pub fn next_event<T>(&self) -> Result<NextEvent<'_, T>, ListenError<()>>
+    where
+        T: Sendable<M>,
+        Self: Sync,
+    {
+        let state = AwaitState::<T> { event: None, waker: None };
+        let result = self.insert_listener::<T, _>(state, Box::new, Self::await_caller::<T>, false, false, false);
+        let id = result.map_err(|error| error.map(|_| ()))?;
+        Ok(NextEvent { eventloop: self, id })
+    }
+    /// Removes a previously added listener, observer or late observer, returns `false` if it has already been removed
+    ///
+    /// # Note on removal during dispatch
+    /// A listener can be removed at any time, even while an event is being dispatched or from within the listener
+    /// itself. A removed listener is not invoked anymore; if it is currently running, the current invocation completes
+    /// normally.
+    ///
+    /// # Note on listener order
+    /// Listeners are invoked in slot order. The slot of a removed listener is reused by the next listener that is
+    /// added, so a new listener may be invoked before listeners that have been added earlier; use
+    /// [`Self::listen_front`] to explicitly add a listener in front of the existing ones.
+    pub fn unlisten(&self, id: ListenerId) -> bool {
+        let removed = self.listeners.scope(|listeners| {
+            // Ensure the listener still exists, since the slot may have been reused or the listener may have moved
+            match find_listener(listeners.as_ref(), id.generation, id.index) {
+                Some(index) => listeners.remove(index).is_some(),
+                None => false,
+            }
+        });
+
+        // Late observers share the generations with the listeners, so the handle may also refer to a late observer
+        removed
+            || self.after.scope(|after| match Self::find_after(after, id.generation, id.index) {
+                Some(index) => after.remove(index).is_some(),
+                None => false,
+            })
     }
     /// Adds a listener to the event loop which receives all events of type `T`, and sends `event` to ensure that the
     /// listener is at least called once
     ///
-    /// This method is especially useful to bootstrap periodical event sources (e.g. timers).
+    /// This method is especially useful to bootstrap periodical event sources (e.g. timers). If the seed event cannot
+    /// be sent, the listener is removed again, and the error carries the seed event.
     ///
     /// # Note on multiple listeners
     /// It is possible to chain multiple listeners for the same event type `T`. If the first invoked listener returns
     /// `Some(event)` again, the next listener is invoked with `event`, and so on. If at some point a listener returns
     /// `None`, the chain ends and subsequent listeners are not invoked anymore.
-    pub fn bootstrap<T>(&self, event: T, callback: fn(T) -> Option<T>) -> Result<(), T>
+    pub fn bootstrap<T>(
+        &self,
+        event: T,
+        callback: fn(T) -> Option<T>,
+    ) -> Result<ListenerId, BootstrapError<T, FnCallback<T>>>
     where
-        T: 'static,
+        T: Sendable<M>,
     {
         // Register the listener
-        if self.register(callback).is_err() {
-            return Err(event);
+        let id = match self.listen(callback) {
+            Ok(id) => id,
+            Err(error) => return Err(BootstrapError::Listen { error, seed: event }),
         };
 
-        // Send the seed event
-        self.send(event)
+        // Send the seed event or roll back the registration
+        if let Err(event) = self.send(event) {
+            self.unlisten(id);
+            return Err(BootstrapError::BacklogFull { callback, seed: event });
+        }
+        Ok(id)
     }
-    /// Sends an event to the event loop, returns `Err(event)` if the backlog is reached
-    pub fn send<T>(&self, event: T) -> Result<(), T>
+    /// Adds a listener to the event loop which receives all events of type `T`, and sends all `events` to seed the
+    /// listener, returns [`BootstrapError::Listen`] with the seed events if the listener cannot be registered
+    ///
+    /// On success, the listener handle is returned together with the first seed event that did not fit into the
+    /// backlog, if any; subsequent seed events are not consumed. The listener remains registered in this case, since
+    /// the previous seed events have already been enqueued. See [`Self::send_all`] for the enqueueing semantics.
+    pub fn bootstrap_many<T, I>(
+        &self,
+        events: I,
+        callback: fn(T) -> Option<T>,
+    ) -> Result<(ListenerId, Option<T>), BootstrapError<I, FnCallback<T>>>
     where
-        T: 'static,
+        T: Sendable<M>,
+        I: IntoIterator<Item = T>,
     {
-        // Insert the event
-        let event_box = Box::new(event)?;
-        if let Err(event_box) = self.events.scope(|events| events.push(event_box)) {
-            return Err(event_box.into_inner().expect("failed to unwrap event"));
+        // Register the listener once and send the seed events
+        let id = match self.listen(callback) {
+            Ok(id) => id,
+            Err(error) => return Err(BootstrapError::Listen { error, seed: events }),
         };
+        let overflow = self.send_all(events).err().map(|(_, event)| event);
+        Ok((id, overflow))
+    }
+    /// Sets a hook that is called with every event that has not been consumed by any listener, right before the event
+    /// is dropped; replaces the previous hook if any
+    ///
+    /// This is useful to diagnose lost events, e.g. by logging the event type or by panicking in debug builds. An event
+    /// is unhandled if there is no matching listener, or if the last listener in the chain returned `Some(event)`.
+    pub fn on_unhandled(&self, hook: fn(&Box<STACKBOX_SIZE>)) {
+        self.unhandled_hook.scope(|unhandled_hook| *unhandled_hook = Some(hook));
+    }
+
+    /// Sets a recorder that records a snapshot of every dispatched event of an allowed type; replaces the previous
+    /// recorder if any
+    ///
+    /// See [`Recorder`] for the safety contract of recordable events.
+    pub fn set_recorder<const DEPTH: usize, const TYPES_MAX: usize, RR>(
+        &self,
+        recorder: &'static Recorder<STACKBOX_SIZE, DEPTH, TYPES_MAX, RR>,
+    ) where
+        RR: Runtime,
+    {
+        self.recorder.scope(|current| *current = Some(recorder));
+    }
 
-        // Trigger a hardware event
-        unsafe { runtime::_runtime_sendevent_ZMWrWpGO() };
+    /// Sends an event to the event loop, returns `Err(event)` if the backlog is reached
+    pub fn send<T>(&self, event: T) -> Result<(), T>
+    where
+        T: Sendable<M>,
+    {
+        self.send_with(0, PendingEvent::new(Box::new(event)?), RingBuf::push)?;
         Ok(())
     }
+    /// Sends an event to the event loop and returns the amount of free lowest-priority backlog slots that are left,
+    /// returns `Err(event)` if the backlog is reached
+    ///
+    /// This is useful for simple flow control, e.g. a producer can stop reading from a peripheral if the backlog has
+    /// just been filled (i.e. `Ok(0)`) until the event loop catches up. Reserved slots do not count as free slots.
+    pub fn try_send<T>(&self, event: T) -> Result<usize, T>
+    where
+        T: Sendable<M>,
+    {
+        self.send_with(0, PendingEvent::new(Box::new(event)?), RingBuf::push)
+    }
+    /// Sends an event to the event loop if there is at least one listener for events of type `T`, returns an error if
+    /// there is no listener or the backlog is reached
+    ///
+    /// Observers do not count as listeners here, since they never consume the event; an event that would only be seen
+    /// by observers is rejected with [`SendError::NoListener`] (see [`Self::has_listener`]).
+    ///
+    /// # Note on listener removal
+    /// The listeners are only checked when the event is sent; if all matching listeners are removed before the event is
+    /// processed, the event is still dropped silently.
+    pub fn send_checked<T>(&self, event: T) -> Result<(), SendError<T>>
+    where
+        T: Sendable<M>,
+    {
+        // Ensure there is a listener before wasting a backlog slot
+        if !self.has_listener::<T>() {
+            return Err(SendError::NoListener(event));
+        }
+        self.send(event).map_err(SendError::BacklogFull)
+    }
+    /// Creates a lightweight handle to send events of type `T` to this event loop
+    pub fn sender<T>(&self) -> Sender<'_, T>
+    where
+        T: Sendable<M>,
+        Self: Sync,
+    {
+        Sender { eventloop: self }
+    }
+    /// Reserves a slot in the lowest-priority backlog for an event of type `T`, returns `None` if the backlog is full
+    /// or if `T` is larger than `STACKBOX_SIZE`
+    ///
+    /// Committing the reservation cannot fail, so this provides a lossless send path e.g. for interrupt handlers, where
+    /// a failed send cannot be propagated. The reserved slot is not available to other senders until the reservation is
+    /// committed or dropped.
+    pub fn reserve<T>(&self) -> Option<Reservation<'_, T>>
+    where
+        T: Sendable<M>,
+        Self: Sync,
+    {
+        // Validate that the event fits into a box, so that committing cannot fail
+        if !Self::event_fits::<T>() {
+            return None;
+        }
 
-    /// Enters the event loop
-    pub fn enter(&self) -> ! {
-        'event_loop: loop {
-            // Wait for event
-            let mut maybe_event_box = self.events.scope(|events| events.pop());
-            if maybe_event_box.is_none() {
-                // Wait for a hardware event and continue
-                unsafe { runtime::_runtime_waitforevent_r3iRR3iR() };
-                continue 'event_loop;
-            }
-
-            // Invoke matching event listeners
-            let listeners = self.listeners.scope(|listeners| *listeners);
-            for listener in listeners {
-                // Grab event box
-                let Some(event_box) = maybe_event_box.take() else {
-                    continue 'event_loop;
+        // Reserve the slot
+        let reserved = self.events.scope(|events| {
+            let has_unreserved_slot = self.has_unreserved_slot(&events[0]);
+            if has_unreserved_slot {
+                self.reserved.scope(|reserved| *reserved += 1);
+            }
+            has_unreserved_slot
+        });
+        // The reservation must only be created on success, since dropping it releases a slot
+        reserved.then(|| Reservation { eventloop: self })
+    }
+    /// Sends multiple events to the event loop at once, returns the index and the value of the first event that could
+    /// not be sent if the backlog is reached
+    ///
+    /// All events are enqueued within a single threadsafe scope and the hardware event is raised only once, which is
+    /// more efficient than sending the events one by one. Events after the first failing event are not consumed from
+    /// the iterator. Since the iterator is advanced within the threadsafe scope, it must not call back into the event
+    /// loop.
+    pub fn send_all<T, I>(&self, events: I) -> Result<(), (usize, T)>
+    where
+        T: Sendable<M>,
+        I: IntoIterator<Item = T>,
+    {
+        // Insert the events; the remaining iterator is dropped outside of the threadsafe scope
+        let mut events = events.into_iter();
+        let (enqueued, failed) = self.events.scope(|backlog| {
+            let mut enqueued = 0;
+            for event in events.by_ref() {
+                let event_box = match Box::new(event) {
+                    Ok(event_box) => event_box,
+                    Err(event) => return (enqueued, Some(event)),
                 };
-
-                // Check if the event type matches the callback's type
-                let EventListener { type_id, callback_box, caller } = listener;
-                if type_id == event_box.inner_type_id() {
-                    // Call the callback and store the returned event box
-                    maybe_event_box = caller(event_box, callback_box);
-                } else {
-                    // This callback cannot process the box; re-insert it for the next potential match
-                    maybe_event_box = Some(event_box);
+                let pushed = match self.has_unreserved_slot(&backlog[0]) {
+                    true => backlog[0].push(PendingEvent::new(event_box)).map_err(|pending| pending.event_box),
+                    false => Err(event_box),
+                };
+                if let Err(event_box) = pushed {
+                    return (enqueued, Some(event_box.into_inner().expect("failed to unwrap event")));
                 }
+                enqueued += 1;
             }
+            (enqueued, None)
+        });
+        #[cfg(feature = "metrics")]
+        self.metrics.scope(|metrics| {
+            metrics.enqueued += enqueued;
+            metrics.dropped += usize::from(failed.is_some());
+        });
+
+        // Trigger a single hardware event; this must happen after the events are enqueued to not race with `enter`
+        if enqueued > 0 {
+            R::send_event();
+        }
+        match failed {
+            Some(event) => Err((enqueued, event)),
+            None => Ok(()),
         }
     }
-
-    /// Calls a callback with an event
-    fn caller<T>(boxed_event: Box<STACKBOX_SIZE>, callback: CopyBox<FPTR_SIZE>) -> Option<Box<STACKBOX_SIZE>>
+    /// Sends an event with the given priority to the event loop, returns `Err(event)` if the backlog of this priority
+    /// is reached
+    ///
+    /// # Panics
+    /// This method panics if `prio` is not less than `PRIORITIES`.
+    pub fn send_prio<T>(&self, prio: u8, event: T) -> Result<(), T>
     where
-        T: 'static,
+        T: Sendable<M>,
     {
-        // Recover the original types
-        let event: T = boxed_event.into_inner().expect("failed to unwrap event");
-        let callback: fn(T) -> Option<T> = callback.inner().expect("failed to unwrap callback");
+        self.send_with(prio, PendingEvent::new(Box::new(event)?), RingBuf::push)?;
+        Ok(())
+    }
+    /// Sends an event to the event loop that is passed to every listener for events of type `T` instead of being passed
+    /// along the listener chain, returns `Err(event)` if the backlog is reached
+    ///
+    /// # Note on broadcast events
+    /// Every consuming listener receives its own clone of the event, and the values returned by the listeners are
+    /// ignored. Observers are still invoked first with a reference to the event.
+    pub fn send_broadcast<T>(&self, event: T) -> Result<(), T>
+    where
+        T: Clone + Sendable<M>,
+    {
+        let event_box = Box::new_cloneable(event)?;
+        self.send_with(0, PendingEvent { event_box, broadcast: true }, RingBuf::push)?;
+        Ok(())
+    }
+    /// Sends an event to the event loop that is only processed after all currently pending events, returns
+    /// `Err(event)` if the deferred backlog is reached
+    ///
+    /// # Note on ordering
+    /// Deferred events are staged separately and are merged into the lowest-priority backlog in FIFO order once all
+    /// pending events have been processed, including events that are sent in the meantime via the non-deferred `send*`
+    /// methods. This gives predictable ordering e.g. for state machines that trigger themselves from within a listener.
+    /// The deferred backlog has its own `BACKLOG_MAX` slots.
+    pub fn send_deferred<T>(&self, event: T) -> Result<(), T>
+    where
+        T: Sendable<M>,
+    {
+        // Insert the event
+        let event_box = Box::new(event)?;
+        if let Err(pending) = self.deferred.scope(|deferred| deferred.push(PendingEvent::new(event_box))) {
+            #[cfg(feature = "metrics")]
+            self.metrics.scope(|metrics| metrics.dropped += 1);
+            return Err(pending.event_box.into_inner().expect("failed to unwrap event"));
+        }
+        #[cfg(feature = "metrics")]
+        self.metrics.scope(|metrics| metrics.enqueued += 1);
 
-        // Call the callback and box the result
-        let event = callback(event)?;
-        let boxed_event = Box::new(event).unwrap_or_else(|_| unreachable!("failed to re-box event"));
-        Some(boxed_event)
+        // Trigger a hardware event; this must happen after the event is enqueued to not race with `enter`
+        R::send_event();
+        Ok(())
+    }
+    /// Sends an urgent event to the front of the lowest-priority backlog so that it is processed next, returns
+    /// `Err(event)` if the backlog is reached
+    ///
+    /// # Note on ordering
+    /// Multiple events sent via this method are processed in LIFO order, i.e. the most recent one is processed first.
+    pub fn send_front<T>(&self, event: T) -> Result<(), T>
+    where
+        T: Sendable<M>,
+    {
+        self.send_with(0, PendingEvent::new(Box::new(event)?), RingBuf::push_front)?;
+        Ok(())
+    }
+
+    /// Sends an event to the event loop unless an equal event is already pending, returns `Err(event)` if the backlog
+    /// is reached
+    ///
+    /// # Note on coalescing
+    /// The event is only compared against events that are currently pending in the backlog (of any priority); an equal
+    /// event that has already been dispatched or is currently being dispatched does not prevent the event from being
+    /// sent.
+    pub fn send_coalesced<T>(&self, event: T) -> Result<(), T>
+    where
+        T: PartialEq + Sendable<M>,
+    {
+        // Insert the event unless an equal event is pending; the duplicate is dropped outside of the threadsafe scope
+        let event_box = Box::new(event)?;
+        let result = self.events.scope(|events| {
+            let event = event_box.as_ref::<T>();
+            match events.iter().any(|events| events.any(|pending| pending.event_box.as_ref::<T>() == event)) {
+                true => Ok(Some(event_box)),
+                false if self.has_unreserved_slot(&events[0]) => {
+                    events[0].push(PendingEvent::new(event_box)).map(|_| None).map_err(|pending| pending.event_box)
+                }
+                false => Err(event_box),
+            }
+        });
+
+        // Check the result
+        match result {
+            Ok(Some(_duplicate_event_box)) => return Ok(()),
+            Ok(None) => {
+                #[cfg(feature = "metrics")]
+                self.metrics.scope(|metrics| metrics.enqueued += 1);
+            }
+            Err(event_box) => {
+                #[cfg(feature = "metrics")]
+                self.metrics.scope(|metrics| metrics.dropped += 1);
+                return Err(event_box.into_inner().expect("failed to unwrap event"));
+            }
+        }
+
+        // Trigger a hardware event; this must happen after the event is enqueued to not race with `enter`
+        R::send_event();
+        Ok(())
+    }
+    /// Updates the pending event of type `T` in place via `update`, or sends a new event created by `make` if no such
+    /// event is pending, returns `Err(event)` with the new event if the backlog is reached
+    ///
+    /// This keeps at most one pending event of type `T` for "latest value wins" events, e.g. sensor readouts. If
+    /// multiple events of type `T` are pending (e.g. sent via [`Self::send`]), only the one that would be processed
+    /// first is updated.
+    ///
+    /// # Note on critical sections
+    /// To not race with other senders, `make` and `update` are called within the threadsafe scope of the backlog, so
+    /// they should be short and must not access the event loop.
+    pub fn send_update<T, F>(&self, make: fn() -> T, update: F) -> Result<(), T>
+    where
+        T: Sendable<M>,
+        F: FnOnce(&mut T),
+    {
+        // Events that do not fit into a stack box can never be pending
+        if !Box::<STACKBOX_SIZE>::fits::<T>() {
+            return Err(make());
+        }
+
+        // Update the pending event or insert a new one
+        let result = self.events.scope(|events| {
+            let pending =
+                events.iter_mut().rev().flat_map(RingBuf::iter_mut).find_map(|pending| pending.event_box.as_mut::<T>());
+            if let Some(pending) = pending {
+                update(pending);
+                return Ok(false);
+            }
+
+            // Send a new event
+            let event_box = Box::new(make()).unwrap_or_else(|_| unreachable!("failed to box event"));
+            match self.has_unreserved_slot(&events[0]) {
+                true => events[0].push(PendingEvent::new(event_box)).map(|_| true).map_err(|pending| pending.event_box),
+                false => Err(event_box),
+            }
+        });
+
+        // Check the result
+        match result {
+            Ok(false) => return Ok(()),
+            Ok(true) => {
+                #[cfg(feature = "metrics")]
+                self.metrics.scope(|metrics| metrics.enqueued += 1);
+            }
+            Err(event_box) => {
+                #[cfg(feature = "metrics")]
+                self.metrics.scope(|metrics| metrics.dropped += 1);
+                return Err(event_box.into_inner().expect("failed to unwrap event"));
+            }
+        }
+
+        // Trigger a hardware event; this must happen after the event is enqueued to not race with `enter`
+        R::send_event();
+        Ok(())
+    }
+    /// Sends an event to the event loop and drops the oldest pending lowest-priority event if the backlog is full,
+    /// returns `Err(event)` only if the event is larger than `STACKBOX_SIZE` or if all slots are reserved
+    ///
+    /// This is useful for telemetry-like events, where the latest value is more important than the oldest one.
+    pub fn send_overwrite<T>(&self, event: T) -> Result<(), T>
+    where
+        T: Sendable<M>,
+    {
+        // Insert the event; the evicted event is dropped outside of the threadsafe scope
+        let event_box = Box::new(event)?;
+        let result = self.events.scope(|events| {
+            // Evict the oldest event if there is no unreserved slot left
+            let reserved = self.reserved.scope(|reserved| *reserved);
+            if reserved >= BACKLOG_MAX {
+                return Err(event_box);
+            }
+            let evicted = match events[0].len() + reserved >= BACKLOG_MAX {
+                true => events[0].pop(),
+                false => None,
+            };
+            let pushed = events[0].push(PendingEvent::new(event_box));
+            pushed.unwrap_or_else(|_| unreachable!("failed to push into non-full backlog"));
+            Ok(evicted)
+        });
+        let _evicted_event_box = match result {
+            Ok(evicted_event_box) => evicted_event_box,
+            Err(event_box) => {
+                #[cfg(feature = "metrics")]
+                self.metrics.scope(|metrics| metrics.dropped += 1);
+                return Err(event_box.into_inner().expect("failed to unwrap event"));
+            }
+        };
+        #[cfg(feature = "metrics")]
+        self.metrics.scope(|metrics| {
+            metrics.enqueued += 1;
+            metrics.dropped += usize::from(_evicted_event_box.is_some());
+        });
+
+        // Trigger a hardware event; this must happen after the event is enqueued to not race with `enter`
+        R::send_event();
+        Ok(())
+    }
+    /// Sends a `heapless` byte vector as event of type `heapless::Vec<u8, N>` to the event loop, returns `Err(data)` if
+    /// the backlog is reached
+    ///
+    /// The vector is boxed directly without an intermediate copy of its contents. That it fits into a stack box is
+    /// checked at compile time.
+    #[cfg(feature = "heapless")]
+    pub fn send_vec<const N: usize>(&self, data: heapless::Vec<u8, N>) -> Result<(), heapless::Vec<u8, N>>
+    where
+        heapless::Vec<u8, N>: Sendable<M>,
+    {
+        const {
+            let size = mem::size_of::<heapless::Vec<u8, N>>();
+            assert!(size <= STACKBOX_SIZE, "heapless::Vec<u8, N> is too large for STACKBOX_SIZE");
+        };
+        self.send(data)
+    }
+
+    /// Removes all pending events of type `T` from the backlog without dispatching them, and returns up to `N` removed
+    /// events together with the amount of removed events that have been dropped due to a lack of space
+    ///
+    /// Pending events of other types are kept in the backlog in their original order. The removed events are collected
+    /// from the highest priority to the lowest priority, and in FIFO order within a priority.
+    pub fn drain_into<T, const N: usize>(&self) -> (Stack<T, N>, usize)
+    where
+        T: Sendable<M>,
+    {
+        // Collect up to `N` events within a single scope
+        let type_id = TypeId::of::<T>();
+        let is_match = |event_box: &Box<STACKBOX_SIZE>| event_box.inner_type_id() == type_id;
+        let mut drained = Stack::new();
+        self.events.scope(|events| {
+            while !drained.is_full() {
+                let Some(event_box) = Self::take_event(events, is_match) else {
+                    break;
+                };
+                let event = event_box.into_inner().expect("failed to unwrap event");
+                drained.push(event).unwrap_or_else(|_| unreachable!("failed to push into non-full stack"));
+            }
+        });
+
+        // Remove the remaining events one by one, so that they are dropped outside of the threadsafe scope
+        let mut dropped = 0;
+        while let Some(_event_box) = self.events.scope(|events| Self::take_event(events, is_match)) {
+            dropped += 1;
+        }
+        (drained, dropped)
+    }
+
+    /// Removes all pending events of type `T` for which `keep` returns `false` from the backlog without dispatching
+    /// them, and returns the amount of removed events
+    ///
+    /// Pending events of other types are always kept, and the remaining events keep their original order.
+    ///
+    /// # Note on critical sections
+    /// The backlog is filtered in a single pass within its threadsafe scope, so `keep` and the destructors of the
+    /// removed events are called within the threadsafe scope; they should be short and must not access the event loop.
+    pub fn retain_events<T>(&self, keep: fn(&T) -> bool) -> usize
+    where
+        T: Sendable<M>,
+    {
+        // Filter all priorities at once
+        let mut removed = 0;
+        self.events.scope(|events| {
+            for events in events.iter_mut() {
+                events.retain(|pending| {
+                    let retained = pending.event_box.as_ref::<T>().is_none_or(keep);
+                    removed += usize::from(!retained);
+                    retained
+                });
+            }
+        });
+        removed
+    }
+    /// Waits until an event of type `T` is pending, takes it from the backlog without dispatching it, and returns it
+    /// directly to the caller
+    ///
+    /// This allows a synchronous handling of a specific event, e.g. to wait for a response within a driver call. The
+    /// order of the remaining events is preserved, and they are not dispatched while waiting; deferred events are not
+    /// considered. Returns `None` if events of type `T` do not fit into a stack box and can therefore never be sent.
+    ///
+    /// # Note on blocking
+    /// The event must be sent from another context (e.g. an interrupt handler); if this method is called from within a
+    /// listener and the event is only sent by another listener, it waits forever.
+    pub fn process_one_of<T>(&self) -> Option<T>
+    where
+        T: Sendable<M>,
+    {
+        // Events that do not fit into a stack box can never be pending
+        if !Box::<STACKBOX_SIZE>::fits::<T>() {
+            return None;
+        }
+
+        let is_match = |event_box: &Box<STACKBOX_SIZE>| event_box.is::<T>();
+        loop {
+            if let Some(event_box) = self.events.scope(|events| Self::take_event(events, is_match)) {
+                let event = event_box.into_inner().expect("failed to unwrap event");
+                return Some(event);
+            }
+
+            // Wait for a hardware event
+            //  - See `enter_with_idle` for why the check-then-wait sequence cannot lose an event
+            R::wait_for_event();
+        }
+    }
+
+    /// Removes all pending events of all priorities, including deferred events, without dispatching them, and returns
+    /// the amount of removed events
+    ///
+    /// This is useful to rebuild the event loop from scratch, e.g. when switching between major application modes.
+    /// Reserved backlog slots stay reserved.
+    pub fn clear_backlog(&self) -> usize {
+        // Remove the events one by one, so that they are dropped outside of the threadsafe scope
+        let mut removed = 0;
+        while let Some(_event_box) = self.events.scope(|events| events.iter_mut().find_map(RingBuf::pop)) {
+            removed += 1;
+        }
+        while let Some(_event_box) = self.deferred.scope(|deferred| deferred.pop()) {
+            removed += 1;
+        }
+        removed
+    }
+    /// Removes all pending events in processing order and passes them to `f` without dispatching them, e.g. to flush
+    /// and route the backlog during shutdown
+    ///
+    /// Events of higher priorities are passed first, followed by the deferred events. `f` is called outside of the
+    /// threadsafe scope, so it may send new events to the event loop; these are drained as well.
+    pub fn drain_all<F>(&self, mut f: F)
+    where
+        F: FnMut(Box<STACKBOX_SIZE>),
+    {
+        // Take the events one by one, so that `f` is called outside of the threadsafe scope
+        while let Some(pending) = self.events.scope(|events| {
+            let next = events.iter_mut().rev().find_map(RingBuf::pop);
+            next.or_else(|| self.deferred.scope(|deferred| deferred.pop()))
+        }) {
+            f(pending.event_box);
+        }
+    }
+    /// Removes all listeners, observers and late observers, and returns the amount of removed listeners and observers
+    ///
+    /// # Note on removal during dispatch
+    /// Listeners are looked up for every invocation, so it is safe to clear the listeners at any time, even from within
+    /// a listener. A listener that is currently running completes normally, and no further listeners are invoked for
+    /// the current event.
+    pub fn clear_listeners(&self) -> usize {
+        // Remove the listeners one by one, so that they are dropped outside of the threadsafe scope
+        let mut removed = 0;
+        while let Some(_listener) = self.listeners.scope(|listeners| {
+            let index = (0..LISTENERS_MAX).find(|index| listeners.get(*index).is_some())?;
+            listeners.remove(index)
+        }) {
+            removed += 1;
+        }
+
+        // Late observers have no destructors, so they can be dropped within the threadsafe scope
+        self.after.scope(|after| after.clear());
+        removed
+    }
+    /// Takes a snapshot of the listeners, observers and late observers, e.g. to restore them via
+    /// [`Self::restore_listeners`] after they have been removed while the device was in a low-power mode
+    ///
+    /// # Note on captured listeners
+    /// Only listeners with plain function callbacks are captured; listeners with stateful closures (see
+    /// [`Self::listen_fn`] and [`Self::next_event`]) cannot be duplicated and are therefore not part of the snapshot.
+    /// Listeners that are currently being invoked are not captured either.
+    pub fn snapshot_listeners(&self) -> ListenerSnapshot<STACKBOX_SIZE, LISTENERS_MAX, STATE_SIZE> {
+        // Plain function callbacks have no destructors, so they can be cloned within the threadsafe scope
+        let after = self.after.scope(|after| *after);
+        self.listeners.scope(|listeners| ListenerSnapshot::capture(listeners, after))
+    }
+    /// Replaces the listeners with plain function callbacks and the late observers with the ones of `snapshot`, and
+    /// returns the amount of restored listeners, observers and late observers
+    ///
+    /// Listeners with stateful closures are kept. The restored listeners keep their handles, and they are restored into
+    /// their original slots to preserve the listener order; if the original slot has been reused by a stateful listener
+    /// in the meantime, the listener is restored into the first free slot instead. Listeners that do not fit into the
+    /// free slots are not restored.
+    pub fn restore_listeners(&self, snapshot: ListenerSnapshot<STACKBOX_SIZE, LISTENERS_MAX, STATE_SIZE>) -> usize {
+        // Plain function callbacks have no destructors, so they can be dropped within the threadsafe scope
+        let ListenerSnapshot { listeners: mut captured, after: captured_after } = snapshot;
+        let restored = self.listeners.scope(|listeners| {
+            // Remove the listeners with plain function callbacks
+            for index in 0..LISTENERS_MAX {
+                let listener = listeners.get(index).and_then(|listener| listener.callback_box.as_ref());
+                if listener.is_some_and(Box::is_cloneable) {
+                    listeners.remove(index);
+                }
+            }
+
+            // Insert the captured listeners
+            let mut restored = 0;
+            for index in 0..LISTENERS_MAX {
+                let Some(listener) = captured.remove(index) else {
+                    continue;
+                };
+
+                // A listener that is currently being invoked has not been removed and must not be duplicated
+                if listeners.get(index).is_some_and(|existing| existing.generation == listener.generation) {
+                    restored += 1;
+                    continue;
+                }
+                let inserted =
+                    listeners.insert_at(index, listener).or_else(|listener| listeners.insert(listener).map(|_| ()));
+                restored += usize::from(inserted.is_ok());
+            }
+            restored
+        });
+
+        // Late observers have no destructors, so they can be replaced within the threadsafe scope
+        self.after.scope(|after| *after = captured_after);
+        restored + captured_after.len()
+    }
+
+    /// The amount of pending events in the backlog, accumulated over all priorities and including deferred events
+    pub fn backlog_len(&self) -> usize {
+        let pending: usize = self.events.scope(|events| events.iter().map(RingBuf::len).sum());
+        pending + self.deferred.scope(|deferred| deferred.len())
+    }
+    /// Whether the lowest-priority backlog is full, so that sending an event would fail; reserved slots count as full
+    pub fn is_backlog_full(&self) -> bool {
+        self.events.scope(|events| !self.has_unreserved_slot(&events[0]))
+    }
+    /// The amount of registered listeners
+    pub fn listeners_len(&self) -> usize {
+        self.listeners.scope(|listeners| listeners.len())
+    }
+    /// The maximum amount of pending events per priority (i.e. `BACKLOG_MAX`)
+    pub const fn backlog_capacity(&self) -> usize {
+        BACKLOG_MAX
+    }
+    /// The maximum amount of registered listeners (i.e. `LISTENERS_MAX`)
+    pub const fn listeners_capacity(&self) -> usize {
+        LISTENERS_MAX
+    }
+    /// The size of an event stack box in bytes (i.e. `STACKBOX_SIZE`), which limits the size of events
+    pub const fn stackbox_size(&self) -> usize {
+        STACKBOX_SIZE
+    }
+    /// The size of a listener stack box in bytes (i.e. `STATE_SIZE`), which limits the size of listener callbacks
+    pub const fn state_size(&self) -> usize {
+        STATE_SIZE
+    }
+    /// Whether an event of type `T` fits into a stack box, so that sending it does not fail due to its size
+    pub const fn event_fits<T>() -> bool {
+        Box::<STACKBOX_SIZE>::fits::<T>()
+    }
+    /// A snapshot of the event loop metrics
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> EventLoopMetrics {
+        self.metrics.scope(|metrics| *metrics)
+    }
+    /// The amount of registered listeners and observers for events of type `T`
+    ///
+    /// Unlike [`Self::has_listener`], this also counts observers, i.e. all registered entries for `T`.
+    pub fn listener_count<T>(&self) -> usize
+    where
+        T: 'static,
+    {
+        let type_id = TypeId::of::<T>();
+        self.listeners.scope(|listeners| listeners.iter().filter(|listener| listener.type_id == type_id).count())
+    }
+    /// Whether there is at least one registered listener that consumes events of type `T`
+    ///
+    /// Observers are not taken into account, since an event that is only seen by observers remains unhandled.
+    pub fn has_listener<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        let type_id = TypeId::of::<T>();
+        let is_consumer =
+            |listener: &EventListener<STACKBOX_SIZE, STATE_SIZE>| listener.type_id == type_id && !listener.observer;
+        self.listeners.scope(|listeners| listeners.iter().any(is_consumer))
+    }
+
+    /// Processes at most one pending event without waiting, returns `false` if there was no pending event
+    ///
+    /// This method is useful to interleave the event loop with other work, e.g. `loop { work(); while l.poll() {} }`.
+    pub fn poll(&self) -> bool {
+        self.poll_if(|_| true)
+    }
+    /// Processes the next pending event without waiting if `ready` returns `true` for it, returns `false` if there was
+    /// no pending event or if the event is not ready yet
+    ///
+    /// An event that is not ready stays at the front of its backlog without being dropped, e.g. to defer processing
+    /// until a peripheral is ready. Since `ready` is called within the threadsafe scope, it must not call back into the
+    /// event loop.
+    pub fn poll_if<F>(&self, ready: F) -> bool
+    where
+        F: FnOnce(&Box<STACKBOX_SIZE>) -> bool,
+    {
+        // Take the next event of the highest non-empty priority if it is ready, and merge the deferred events once the
+        // backlog has been drained
+        let Some(pending) = self.events.scope(|events| {
+            if events.iter().all(RingBuf::is_empty) {
+                self.merge_deferred(&mut events[0]);
+            }
+            let events = events.iter_mut().rev().find(|events| !events.is_empty())?;
+            events.pop_if(|pending| ready(&pending.event_box))
+        }) else {
+            return false;
+        };
+
+        // Dispatch the event
+        #[cfg(feature = "metrics")]
+        self.metrics.scope(|metrics| metrics.dispatched += 1);
+        self.dispatch(pending);
+        true
+    }
+
+    /// Processes pending events until the backlog is empty, including events that are sent during processing
+    pub fn run_until_idle(&self) {
+        // Process events until the backlog is drained
+        while self.poll() {}
+    }
+    /// Processes pending events until the backlog is empty or `max_events` have been processed, returns the amount of
+    /// processed events
+    ///
+    /// This method is especially useful for tests, where a listener that keeps re-sending events would otherwise stall
+    /// [`Self::run_until_idle`].
+    pub fn run_max(&self, max_events: usize) -> usize {
+        let mut processed = 0;
+        while processed < max_events && self.poll() {
+            processed += 1;
+        }
+        processed
+    }
+
+    /// Enters the event loop
+    pub fn enter(&self) -> ! {
+        self.enter_with_idle(|| {})
+    }
+    /// Enters the event loop and calls `on_idle` each time the backlog becomes empty, right before the loop waits for
+    /// the next hardware event
+    ///
+    /// The hook is called once per transition to idle, not on every wakeup. Events sent by the hook are processed
+    /// before the loop goes to sleep. If a dispatch budget is set (see [`Self::with_dispatch_budget`]), the hook is
+    /// also called each time the budget is exhausted.
+    pub fn enter_with_idle(&self, on_idle: fn()) -> ! {
+        let mut idle = false;
+        loop {
+            // Drain the backlog until the dispatch budget is exhausted
+            let mut dispatched = 0;
+            while dispatched < self.dispatch_budget && self.poll() {
+                idle = false;
+                dispatched += 1;
+            }
+
+            // Run the idle hook on the transition to idle or if the budget is exhausted, and re-check the backlog
+            // afterwards
+            if !idle {
+                idle = true;
+                on_idle();
+                continue;
+            }
+
+            // Wait for a hardware event
+            //  - The last action before waiting is the check that the backlog is empty. Since `send` enqueues before it
+            //    raises the hardware event, and the runtime latches raised events until the next wait returns, an event
+            //    that is sent at any point after this check wakes the wait immediately instead of getting lost.
+            R::wait_for_event();
+        }
+    }
+
+    /// Enters the event loop and calls `on_tick` whenever `should_wake` returns `true` after the backlog has been
+    /// drained, e.g. to service a periodic control task
+    ///
+    /// # Note on ticks
+    /// The event loop has no timer, so the predicate is only checked when the loop wakes up. The application must
+    /// therefore configure a periodic interrupt (e.g. SysTick) which marks the tick as due and then raises a hardware
+    /// event via the runtime (e.g. `sev` on Cortex-M); otherwise, ticks are only noticed when an event is sent. Events
+    /// sent by `on_tick` are processed before the predicate is checked again.
+    pub fn enter_with_tick(&self, should_wake: fn() -> bool, on_tick: fn()) -> ! {
+        loop {
+            // Drain the backlog
+            while self.poll() {
+                // Process all pending events
+            }
+
+            // Run the tick hook if it is due, and re-check the backlog afterwards
+            if should_wake() {
+                on_tick();
+                continue;
+            }
+
+            // Wait for a hardware event
+            //  - The last action before waiting is the check of the predicate; an interrupt that marks a tick as due
+            //    also raises a hardware event, which stays latched until the next wait (see `enter_with_idle`).
+            R::wait_for_event();
+        }
+    }
+
+    /// Enters the event loop until `should_stop` returns `true`
+    ///
+    /// The predicate is checked before each event is processed and whenever the loop wakes up, so it should be updated
+    /// from within a listener or be followed by an event or wakeup to be noticed. Events that are still pending when
+    /// the loop stops are kept in the backlog and are processed by the next call to `enter*`, `poll` or `run*`.
+    pub fn enter_until(&self, should_stop: fn() -> bool) {
+        loop {
+            // Check the predicate before each event
+            if should_stop() {
+                return;
+            }
+
+            // Process the next event if any
+            if self.poll() {
+                continue;
+            }
+
+            // Wait for a hardware event
+            //  - The last action before waiting is the check that the backlog is empty, so an event that is sent after
+            //    this check wakes the wait immediately instead of getting lost (see `enter_with_idle`).
+            R::wait_for_event();
+        }
+    }
+
+    /// Takes the first pending event that matches `predicate` from the highest priority backlog that contains such an
+    /// event, and keeps the order of the remaining events
+    fn take_event<P>(
+        events: &mut [RingBuf<PendingEvent<STACKBOX_SIZE>, BACKLOG_MAX>; PRIORITIES],
+        mut predicate: P,
+    ) -> Option<Box<STACKBOX_SIZE>>
+    where
+        P: FnMut(&Box<STACKBOX_SIZE>) -> bool,
+    {
+        for events in events.iter_mut().rev() {
+            // Rotate the backlog once and take the first matching event
+            let mut taken = None;
+            for _ in 0..events.len() {
+                let pending = events.pop().expect("missing pending event");
+                match taken.is_none() && predicate(&pending.event_box) {
+                    true => taken = Some(pending.event_box),
+                    false => events.push(pending).unwrap_or_else(|_| unreachable!("failed to re-push event")),
+                }
+            }
+
+            // Return the event if any
+            if taken.is_some() {
+                return taken;
+            }
+        }
+        None
+    }
+
+    /// Sends a boxed event to the lowest-priority backlog of the event loop, returns `Err(event_box)` if the backlog is
+    /// reached
+    ///
+    /// Broadcast events must be cloneable.
+    pub(crate) fn send_box(&self, event_box: Box<STACKBOX_SIZE>, broadcast: bool) -> Result<(), Box<STACKBOX_SIZE>> {
+        debug_assert!(!broadcast || event_box.is_cloneable(), "broadcast event is not cloneable");
+        self.send_box_with(0, PendingEvent { event_box, broadcast }, RingBuf::push)?;
+        Ok(())
+    }
+    /// Sends a boxed event of type `T` with the given priority to the event loop using the given `push` implementation,
+    /// and returns the amount of free slots that are left in the backlog of this priority
+    fn send_with<T>(
+        &self,
+        prio: u8,
+        event: PendingEvent<STACKBOX_SIZE>,
+        push: PushFn<STACKBOX_SIZE, BACKLOG_MAX>,
+    ) -> Result<usize, T>
+    where
+        T: 'static,
+    {
+        let result = self.send_box_with(prio, event, push);
+        result.map_err(|event_box| event_box.into_inner().expect("failed to unwrap event"))
+    }
+    /// Sends a boxed event with the given priority to the event loop using the given `push` implementation, and returns
+    /// the amount of free slots that are left in the backlog of this priority
+    fn send_box_with(
+        &self,
+        prio: u8,
+        event: PendingEvent<STACKBOX_SIZE>,
+        push: PushFn<STACKBOX_SIZE, BACKLOG_MAX>,
+    ) -> Result<usize, Box<STACKBOX_SIZE>> {
+        // Insert the event; the evicted event is dropped outside of the threadsafe scope
+        assert!(usize::from(prio) < PRIORITIES, "invalid event priority");
+        let result = self.events.scope(|events| {
+            // Reserved slots only exist in the lowest-priority backlog and are never evicted
+            let reserved = match prio {
+                0 => self.reserved.scope(|reserved| *reserved),
+                _ => 0,
+            };
+            let backlog = &mut events[usize::from(prio)];
+            let is_full = backlog.len() + reserved >= BACKLOG_MAX;
+            let evicted = match self.policy == OverflowPolicy::DropOldest && is_full && reserved < BACKLOG_MAX {
+                true => backlog.pop(),
+                false => None,
+            };
+
+            // Insert the event if there is a free slot
+            if backlog.len() + reserved >= BACKLOG_MAX {
+                return Err(event);
+            }
+            push(backlog, event).map(|_| (BACKLOG_MAX - backlog.len() - reserved, evicted))
+        });
+        let (free_slots, _evicted_event_box) = match result {
+            Ok(result) => result,
+            Err(event) => {
+                #[cfg(feature = "metrics")]
+                self.metrics.scope(|metrics| metrics.dropped += 1);
+                assert!(self.policy != OverflowPolicy::Panic, "backlog of priority {prio} is full");
+                return Err(event.event_box);
+            }
+        };
+        #[cfg(feature = "metrics")]
+        self.metrics.scope(|metrics| {
+            metrics.enqueued += 1;
+            metrics.dropped += usize::from(_evicted_event_box.is_some());
+        });
+
+        // Trigger a hardware event; this must happen after the event is enqueued to not race with `enter`
+        R::send_event();
+        Ok(free_slots)
+    }
+    /// Moves the deferred events into the lowest-priority backlog `events` as long as there are unreserved free slots
+    ///
+    /// This must be called within the threadsafe scope of the backlog.
+    fn merge_deferred(&self, events: &mut RingBuf<PendingEvent<STACKBOX_SIZE>, BACKLOG_MAX>) {
+        self.deferred.scope(|deferred| {
+            while !deferred.is_empty() && self.has_unreserved_slot(events) {
+                let pending = deferred.pop().expect("missing deferred event");
+                events.push(pending).unwrap_or_else(|_| unreachable!("failed to push into free slot"));
+            }
+        });
+    }
+    /// Whether the lowest-priority backlog `events` has a free slot that is not reserved
+    ///
+    /// This must be called within the threadsafe scope of the backlog, so that the reservations cannot change until the
+    /// slot is used.
+    fn has_unreserved_slot(&self, events: &RingBuf<PendingEvent<STACKBOX_SIZE>, BACKLOG_MAX>) -> bool {
+        self.unreserved_slots(events) > 0
+    }
+    /// The amount of free slots in the lowest-priority backlog `events` that are not reserved
+    fn unreserved_slots(&self, events: &RingBuf<PendingEvent<STACKBOX_SIZE>, BACKLOG_MAX>) -> usize {
+        let reserved = self.reserved.scope(|reserved| *reserved);
+        BACKLOG_MAX - events.len() - reserved
+    }
+
+    /// Adds a listener with the given callback and caller for events of type `T` to the event loop
+    ///
+    /// The callback is boxed via `boxer`; plain function callbacks are boxed as cloneable, so that they can be
+    /// captured by [`Self::snapshot_listeners`].
+    fn insert_listener<T, F>(
+        &self,
+        callback: F,
+        boxer: fn(F) -> Result<Box<STATE_SIZE>, F>,
+        caller: Caller<STACKBOX_SIZE, STATE_SIZE>,
+        once: bool,
+        observer: bool,
+        front: bool,
+    ) -> Result<ListenerId, ListenError<F>>
+    where
+        T: 'static,
+        F: 'static,
+    {
+        // Box the callback
+        let callback_box =
+            boxer(callback).map_err(|callback| ListenError::TooLarge { callback, available: STATE_SIZE })?;
+        let generation = self.next_generation();
+        let listener = EventListener {
+            type_id: TypeId::of::<T>(),
+            generation,
+            once,
+            observer,
+            callback_box: Some(callback_box),
+            caller,
+        };
+
+        // Insert the listener unless there is already a listener for the type and the listeners must be unique
+        let type_id = listener.type_id;
+        let inserted = self.listeners.scope(|listeners| {
+            let is_duplicate = self.unique_listeners && self.has_any_listener(listeners, type_id);
+            match is_duplicate {
+                true => Err((listener, true)),
+                false if front => Self::insert_front(listeners, listener).map_err(|listener| (listener, false)),
+                false => listeners.insert(listener).map_err(|listener| (listener, false)),
+            }
+        });
+        let index = match inserted {
+            Ok(index) => index,
+            Err((listener, is_duplicate)) => {
+                let callback_box = listener.callback_box.expect("missing callback");
+                let callback = callback_box.into_inner().expect("failed to unwrap callback");
+                return Err(match is_duplicate {
+                    true => ListenError::DuplicateType { callback },
+                    false => ListenError::Full { callback, capacity: LISTENERS_MAX },
+                });
+            }
+        };
+        Ok(ListenerId { index, generation })
+    }
+
+    /// Allocates the generation for a new listener or late observer
+    fn next_generation(&self) -> usize {
+        self.generation.scope(|generation| mem::replace(generation, generation.wrapping_add(1)))
+    }
+    /// Whether there is any listener, observer or late observer for events with the given type ID
+    ///
+    /// This must be called within the threadsafe scope of the listeners.
+    fn has_any_listener(
+        &self,
+        listeners: &Slots<EventListener<STACKBOX_SIZE, STATE_SIZE>, LISTENERS_MAX>,
+        type_id: TypeId,
+    ) -> bool {
+        let is_listener = listeners.iter().any(|listener| listener.type_id == type_id);
+        is_listener || self.after.scope(|after| after.iter().any(|after| after.type_id == type_id))
+    }
+    /// Inserts a consuming listener in front of all consuming listeners of the same type, and returns its slot index
+    ///
+    /// The listener is inserted into the first slot that is free or occupied by a consuming listener of its type, and
+    /// each displaced listener moves to the next such slot until a listener is moved into a free slot; this keeps the
+    /// order of the existing listeners and does not move listeners of other types.
+    fn insert_front(
+        listeners: &mut Slots<EventListener<STACKBOX_SIZE, STATE_SIZE>, LISTENERS_MAX>,
+        listener: EventListener<STACKBOX_SIZE, STATE_SIZE>,
+    ) -> Result<usize, EventListener<STACKBOX_SIZE, STATE_SIZE>> {
+        // Ensure that there is a free slot to end the displacement chain
+        if listeners.len() == LISTENERS_MAX {
+            return Err(listener);
+        }
+
+        // Find the slots that are free or occupied by a consuming listener of the same type
+        let (type_id, mut index) = (listener.type_id, None);
+        let mut displaced = Some(listener);
+        for slot in 0..LISTENERS_MAX {
+            let is_chain_slot =
+                listeners.get(slot).is_none_or(|existing| existing.type_id == type_id && !existing.observer);
+            if !is_chain_slot {
+                continue;
+            }
+
+            // Displace the listener in the slot, or stop at the free slot
+            let Some(next) = displaced.take() else {
+                break;
+            };
+            displaced = listeners.remove(slot);
+            listeners.insert_at(slot, next).unwrap_or_else(|_| unreachable!("failed to insert into free slot"));
+            index.get_or_insert(slot);
+        }
+        Ok(index.expect("missing free slot"))
+    }
+    /// Finds the index of the late observer with the given generation, starting with the index `hint` where it was
+    /// last seen
+    fn find_after(
+        after: &Stack<AfterListener<STACKBOX_SIZE>, LISTENERS_MAX>,
+        generation: usize,
+        hint: usize,
+    ) -> Option<usize> {
+        let is_after = |index: &usize| after.get(*index).is_some_and(|after| after.generation == generation);
+        Some(hint).filter(is_after).or_else(|| (0..after.len()).find(is_after))
+    }
+
+    /// Dispatches an event to the matching event listeners
+    fn dispatch(&self, pending: PendingEvent<STACKBOX_SIZE>) {
+        // Record the event before it is passed to the listeners
+        let PendingEvent { event_box, broadcast } = pending;
+        if let Some(recorder) = self.recorder.scope(|recorder| *recorder) {
+            recorder.record(&event_box, broadcast);
+        }
+
+        // Snapshot the event for the late observers, since the listener chain may consume the event
+        let type_id = event_box.inner_type_id();
+        let snapshot =
+            self.after.scope(|after| after.iter().find(|after| after.type_id == type_id).map(|after| after.snapshot));
+        let snapshot_box = snapshot.map(|snapshot| snapshot(&event_box));
+
+        // Invoke the listeners, then the late observers
+        self.dispatch_listeners(event_box, broadcast);
+        if let Some(snapshot_box) = snapshot_box {
+            self.dispatch_after(&snapshot_box);
+        }
+    }
+    /// Invokes the late observers for an event
+    fn dispatch_after(&self, event_box: &Box<STACKBOX_SIZE>) {
+        let (type_id, mut start) = (event_box.inner_type_id(), 0);
+        loop {
+            // Copy the next late observer that matches the event type
+            let next = self.after.scope(|after| {
+                let mut matching = after.iter().enumerate().skip(start);
+                matching.find(|(_, after)| after.type_id == type_id).map(|(index, after)| (index, *after))
+            });
+            let Some((index, after)) = next else {
+                break;
+            };
+
+            // Invoke the late observer; it may remove itself or preceding late observers, so the next late observer is
+            // searched after its current index, or at its former index if it is gone
+            (after.caller)(event_box, &after.callback);
+            let moved_index = self.after.scope(|afters| Self::find_after(afters, after.generation, index));
+            start = moved_index.map_or(index, |moved_index| moved_index + 1);
+        }
+    }
+    /// Passes an event to the observers and the listener chain
+    fn dispatch_listeners(&self, mut event_box: Box<STACKBOX_SIZE>, broadcast: bool) {
+        // Invoke the observers first, then the consuming listener chain
+        let mut broadcasted = false;
+        for observers in [true, false] {
+            let (maybe_event_box, delivered) = dispatch_chain(&self.listeners, event_box, observers, broadcast);
+            broadcasted |= delivered;
+            let Some(next_event_box) = maybe_event_box else {
+                return;
+            };
+            event_box = next_event_box;
+        }
+
+        // Broadcast events are handled if they have been passed to at least one listener
+        if broadcasted {
+            return;
+        }
+
+        // Pass the unhandled event to the hook before it is dropped
+        #[cfg(feature = "metrics")]
+        self.metrics.scope(|metrics| metrics.unhandled += 1);
+        if let Some(unhandled_hook) = self.unhandled_hook.scope(|unhandled_hook| *unhandled_hook) {
+            unhandled_hook(&event_box);
+        }
+    }
+
+    /// Calls a late observer callback with a reference to an event
+    fn after_caller<T>(event_box: &Box<STACKBOX_SIZE>, callback_box: &CopyBox<FPTR_SIZE>)
+    where
+        T: 'static,
+    {
+        let event: &T = event_box.as_ref().expect("failed to borrow event");
+        let callback: fn(&T) = callback_box.inner().expect("failed to unwrap callback");
+        callback(event);
+    }
+    /// Clones an event of type `T` into a new box
+    fn snapshot_event<T>(event_box: &Box<STACKBOX_SIZE>) -> Box<STACKBOX_SIZE>
+    where
+        T: Clone + 'static,
+    {
+        let event: &T = event_box.as_ref().expect("failed to borrow event");
+        Box::new(event.clone()).unwrap_or_else(|_| unreachable!("failed to box event snapshot"))
+    }
+
+    /// Calls an observer callback with a reference to an event and returns the event unchanged
+    fn observer_caller<T>(
+        boxed_event: Box<STACKBOX_SIZE>,
+        boxed_callback: Box<STATE_SIZE>,
+    ) -> (Option<Box<STACKBOX_SIZE>>, Box<STATE_SIZE>)
+    where
+        T: 'static,
+    {
+        // Recover the original types and call the callback
+        let event: &T = boxed_event.as_ref().expect("failed to borrow event");
+        let callback: &fn(&T) = boxed_callback.as_ref().expect("failed to borrow callback");
+        callback(event);
+        (Some(boxed_event), boxed_callback)
+    }
+
+    /// Stores an event in the state of an awaiting listener and wakes the awaiting task, or passes the event on if the
+    /// listener has already received an event
+    fn await_caller<T>(
+        boxed_event: Box<STACKBOX_SIZE>,
+        mut boxed_state: Box<STATE_SIZE>,
+    ) -> (Option<Box<STACKBOX_SIZE>>, Box<STATE_SIZE>)
+    where
+        T: 'static,
+    {
+        // Only keep the first event
+        let state: &mut AwaitState<T> = boxed_state.as_mut().expect("failed to borrow listener state");
+        if state.event.is_some() {
+            return (Some(boxed_event), boxed_state);
+        }
+
+        // Store the event and wake the task
+        state.event = Some(boxed_event.into_inner().expect("failed to unwrap event"));
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        (None, boxed_state)
+    }
+
+    /// Calls a callback with an event if the event matches the predicate, or passes the event on otherwise
+    fn filter_caller<T>(
+        boxed_event: Box<STACKBOX_SIZE>,
+        boxed_callback: Box<STATE_SIZE>,
+    ) -> (Option<Box<STACKBOX_SIZE>>, Box<STATE_SIZE>)
+    where
+        T: 'static,
+    {
+        // Check the predicate without consuming the event
+        let &(predicate, callback): &FilteredCallback<T> = boxed_callback.as_ref().expect("failed to borrow callback");
+        let event: &T = boxed_event.as_ref().expect("failed to borrow event");
+        if !predicate(event) {
+            return (Some(boxed_event), boxed_callback);
+        }
+
+        // Call the callback and box the result
+        let event: T = boxed_event.into_inner().expect("failed to unwrap event");
+        let maybe_event = callback(event);
+        let boxed_event =
+            maybe_event.map(|event| Box::new(event).unwrap_or_else(|_| unreachable!("failed to re-box event")));
+        (boxed_event, boxed_callback)
+    }
+}
+impl<
+        T,
+        const STACKBOX_SIZE: usize,
+        const BACKLOG_MAX: usize,
+        const LISTENERS_MAX: usize,
+        const PRIORITIES: usize,
+        R,
+        const STATE_SIZE: usize,
+        M,
+    > SendEvent<T> for EventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX, PRIORITIES, R, STATE_SIZE, M>
+where
+    T: Sendable<M>,
+    R: Runtime,
+{
+    fn send_event(&self, event: T) -> Result<(), T> {
+        self.send(event)
+    }
+}
+impl<
+        T,
+        const STACKBOX_SIZE: usize,
+        const BACKLOG_MAX: usize,
+        const LISTENERS_MAX: usize,
+        const PRIORITIES: usize,
+        R,
+        const STATE_SIZE: usize,
+        M,
+    > ReserveEvent<T> for EventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX, PRIORITIES, R, STATE_SIZE, M>
+where
+    T: Sendable<M>,
+    R: Runtime,
+{
+    fn commit_reserved(&self, event: T) {
+        // Insert the event into the reserved slot
+        let event_box = Box::new(event).unwrap_or_else(|_| unreachable!("failed to box reserved event"));
+        self.events.scope(|events| {
+            self.reserved.scope(|reserved| *reserved -= 1);
+            let pushed = events[0].push(PendingEvent::new(event_box));
+            pushed.unwrap_or_else(|_| unreachable!("failed to push into reserved slot"));
+        });
+        #[cfg(feature = "metrics")]
+        self.metrics.scope(|metrics| metrics.enqueued += 1);
+
+        // Trigger a hardware event; this must happen after the event is enqueued to not race with `enter`
+        R::send_event();
+    }
+    fn release_reserved(&self) {
+        self.reserved.scope(|reserved| *reserved -= 1);
+    }
+}
+impl<
+        T,
+        const STACKBOX_SIZE: usize,
+        const BACKLOG_MAX: usize,
+        const LISTENERS_MAX: usize,
+        const PRIORITIES: usize,
+        R,
+        const STATE_SIZE: usize,
+        M,
+    > AwaitEvent<T> for EventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX, PRIORITIES, R, STATE_SIZE, M>
+where
+    T: Sendable<M>,
+    R: Runtime,
+{
+    fn poll_event(&self, id: ListenerId, waker: &Waker) -> Poll<Option<T>> {
+        // Take the event or replace the waker; the removed listener and the previous waker are dropped outside the
+        // scope
+        let (poll, _removed_listener, _previous_waker) = self.listeners.scope(|listeners| {
+            // The listener may have been removed, e.g. via `unlisten` or `clear_listeners`
+            let Some(index) = find_listener(listeners.as_ref(), id.generation, id.index) else {
+                return (Some(Poll::Ready(None)), None, None);
+            };
+            let listener = listeners.get_mut(index).expect("missing listener");
+            let Some(boxed_state) = listener.callback_box.as_mut() else {
+                // The listener is currently being invoked, so it cannot be accessed
+                return (None, None, None);
+            };
+
+            // Take the event and remove the listener, or replace the waker
+            let state: &mut AwaitState<T> = boxed_state.as_mut().expect("failed to borrow listener state");
+            match state.event.take() {
+                Some(event) => (Some(Poll::Ready(Some(event))), listeners.remove(index), None),
+                None => (Some(Poll::Pending), None, state.waker.replace(waker.clone())),
+            }
+        });
+
+        // Poll again once the invocation is complete if the listener is currently being invoked
+        poll.unwrap_or_else(|| {
+            waker.wake_by_ref();
+            Poll::Pending
+        })
+    }
+    fn cancel_event(&self, id: ListenerId) {
+        self.unlisten(id);
+    }
+}
+#[cfg(feature = "defmt")]
+impl<
+        const STACKBOX_SIZE: usize,
+        const BACKLOG_MAX: usize,
+        const LISTENERS_MAX: usize,
+        const PRIORITIES: usize,
+        R,
+        const STATE_SIZE: usize,
+        M,
+    > defmt::Format for EventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX, PRIORITIES, R, STATE_SIZE, M>
+where
+    R: Runtime,
+{
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "EventLoop {{ backlog_len: {=usize}, listeners_len: {=usize} }}",
+            self.backlog_len(),
+            self.listeners_len()
+        );
+    }
+}
+impl<
+        const STACKBOX_SIZE: usize,
+        const BACKLOG_MAX: usize,
+        const LISTENERS_MAX: usize,
+        const PRIORITIES: usize,
+        R,
+        const STATE_SIZE: usize,
+        M,
+    > Default for EventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX, PRIORITIES, R, STATE_SIZE, M>
+where
+    R: Runtime,
+{
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -2,10 +2,18 @@
 #![doc = include_str!("../README.md")]
 
 pub mod boxes;
+pub mod channel;
 pub mod collections;
 pub mod eventloop;
-mod runtime;
+mod macros;
+pub mod recorder;
+pub mod runtime;
+pub mod scoped;
+pub mod sliced;
 pub mod threadsafe;
+pub mod timer;
 
-// Re-export the event loop
+// Re-export the event loops
 pub use eventloop::*;
+pub use scoped::ScopedEventLoop;
+pub use sliced::SliceEventLoop;
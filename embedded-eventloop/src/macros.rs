@@ -0,0 +1,116 @@
+//! Convenience macros
+
+/// Adds a listener for each `EventType => handler` arm to an event loop, and returns a tuple with the results of the
+/// individual [`EventLoop::listen`](crate::EventLoop::listen) calls in order
+///
+/// Each handler must be a function with the signature `fn(EventType) -> Option<EventType>`; this is type-checked for
+/// every arm. The event loop expression is evaluated only once.
+///
+/// # Example
+/// ```ignore
+/// let (on_tick, on_input) = embedded_eventloop::listen!(EVENTLOOP, Tick => on_tick, Input => on_input);
+/// ```
+#[macro_export]
+macro_rules! listen {
+    ($eventloop:expr, $($event:ty => $handler:expr),+ $(,)?) => {
+        match &$eventloop {
+            eventloop => ($(eventloop.listen::<$event>($handler),)+),
+        }
+    };
+}
+
+/// Defines a mock runtime module `mock_runtime` that provides the runtime-specific function stubs for host tests
+///
+/// The behavior of `wait_for_event` and `send_event` can be set per thread via `mock_runtime::set_wait_for_event` and
+/// `mock_runtime::set_send_event`, e.g. to count raised events; by default, waiting yields the current thread and
+/// raising an event is a no-op. The threadsafe context is a process-wide reentrant lock.
+///
+/// # Note on linking
+/// The macro must be invoked at most once per binary, and the binary must not link another runtime crate (e.g.
+/// `embedded-eventloop-std`), since the function stubs are global symbols. The expansion requires `std`.
+///
+/// # Example
+/// ```ignore
+/// embedded_eventloop::mock_runtime!();
+///
+/// #[test]
+/// fn test() {
+///     mock_runtime::set_send_event(count_sev);
+///     // ...
+/// }
+/// ```
+#[macro_export]
+macro_rules! mock_runtime {
+    () => {
+        /// A mock runtime whose behavior can be set per thread
+        #[allow(dead_code)]
+        pub mod mock_runtime {
+            use ::std::{
+                cell::Cell,
+                sync::{Mutex, PoisonError},
+                thread,
+            };
+
+            ::std::thread_local! {
+                /// The `wait_for_event` implementation of the current thread
+                static WAIT_FOR_EVENT: Cell<fn()> = const { Cell::new(thread::yield_now) };
+                /// The `send_event` implementation of the current thread
+                static SEND_EVENT: Cell<fn()> = const { Cell::new(noop) };
+                /// The nesting depth of the threadsafe context on the current thread
+                static DEPTH: Cell<usize> = const { Cell::new(0) };
+            }
+
+            /// The process-wide lock for the threadsafe context
+            static LOCK: Mutex<()> = Mutex::new(());
+
+            /// Does nothing
+            fn noop() {
+                // No-op
+            }
+
+            /// Sets the `wait_for_event` implementation of the current thread
+            pub fn set_wait_for_event(wait_for_event: fn()) {
+                WAIT_FOR_EVENT.set(wait_for_event);
+            }
+            /// Sets the `send_event` implementation of the current thread
+            pub fn set_send_event(send_event: fn()) {
+                SEND_EVENT.set(send_event);
+            }
+
+            /// Calls the `wait_for_event` implementation of the current thread
+            #[no_mangle]
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            pub fn _runtime_waitforevent_r3iRR3iR() {
+                WAIT_FOR_EVENT.get()();
+            }
+            /// Calls the `send_event` implementation of the current thread
+            #[no_mangle]
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            pub fn _runtime_sendevent_ZMWrWpGO() {
+                SEND_EVENT.get()();
+            }
+            /// Runs `code` within a process-wide reentrant lock
+            #[no_mangle]
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            pub fn _runtime_threadsafe_NfpNM21J(code: &mut dyn FnMut()) {
+                /// Restores the previous nesting depth, even if `code` panics
+                struct Depth(usize);
+                impl Drop for Depth {
+                    fn drop(&mut self) {
+                        DEPTH.set(self.0);
+                    }
+                }
+
+                // Only the outermost context acquires the lock
+                let depth = DEPTH.get();
+                let _lock = (depth == 0).then(|| LOCK.lock().unwrap_or_else(PoisonError::into_inner));
+                let _depth = Depth(depth);
+                DEPTH.set(depth + 1);
+                code();
+            }
+        }
+    };
+}
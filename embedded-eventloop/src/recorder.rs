@@ -0,0 +1,162 @@
+//! An event recorder to replay the most recently dispatched events, e.g. for post-mortem debugging
+
+use crate::{
+    boxes::Box,
+    collections::{RingBuf, Stack},
+    runtime::{LinkedRuntime, Runtime},
+    threadsafe::ThreadSafeCell,
+    EventLoop,
+};
+use core::{
+    any::TypeId,
+    fmt::{self, Debug, Formatter},
+};
+
+/// A type-erased recorder for events of an event loop with the given stack box size
+pub(crate) trait RecordEvent<const STACKBOX_SIZE: usize>: Debug {
+    /// Records a snapshot of `event_box` if its type is recordable, together with whether it is a broadcast event
+    fn record(&self, event_box: &Box<STACKBOX_SIZE>, broadcast: bool);
+}
+
+/// A recorded event
+#[derive(Debug)]
+struct Record<const STACKBOX_SIZE: usize> {
+    /// The snapshot of the event
+    snapshot: Box<STACKBOX_SIZE>,
+    /// Whether the event has been sent as broadcast event
+    broadcast: bool,
+}
+
+/// An event recorder that keeps snapshots of the last `DEPTH` dispatched events of the allowed types
+///
+/// # Safety contract
+/// The recorder snapshots each event by copying its bytes, including padding bytes, and replays the snapshots as they
+/// are. This is only sound for plain old data events, so only event types that have been allowed via [`Self::allow`]
+/// are recorded; these must be `Copy`, and they must not contain references or pointers that may become dangling before
+/// the events are replayed.
+///
+/// # Note on crashes
+/// To replay the events after a crash, the recorder must be placed in memory that is not cleared on reset (e.g. a
+/// `.uninit` section); the snapshots are only valid for the same firmware build, since type IDs are not stable across
+/// builds.
+pub struct Recorder<
+    const STACKBOX_SIZE: usize = 64,
+    const DEPTH: usize = 16,
+    const TYPES_MAX: usize = 8,
+    R: Runtime = LinkedRuntime,
+> {
+    /// The snapshots of the recorded events, oldest first
+    records: ThreadSafeCell<RingBuf<Record<STACKBOX_SIZE>, DEPTH>, R>,
+    /// The type IDs of the event types to record
+    types: ThreadSafeCell<Stack<TypeId, TYPES_MAX>, R>,
+}
+impl<const STACKBOX_SIZE: usize, const DEPTH: usize, const TYPES_MAX: usize, R>
+    Recorder<STACKBOX_SIZE, DEPTH, TYPES_MAX, R>
+where
+    R: Runtime,
+{
+    /// Creates a new recorder
+    pub const fn new() -> Self {
+        Self {
+            records: ThreadSafeCell::with_runtime(RingBuf::new()),
+            types: ThreadSafeCell::with_runtime(Stack::new()),
+        }
+    }
+
+    /// Allows events of type `T` to be recorded, returns `false` if `TYPES_MAX` event types are already allowed
+    pub fn allow<T>(&self) -> bool
+    where
+        T: Copy + 'static,
+    {
+        let type_id = TypeId::of::<T>();
+        self.types.scope(|types| types.iter().any(|allowed| *allowed == type_id) || types.push(type_id).is_ok())
+    }
+
+    /// Re-sends the recorded events to `eventloop` in their original order, returns the amount of events that have been
+    /// sent
+    ///
+    /// The snapshots are kept, and replaying stops at the first event that does not fit into the backlog. Broadcast
+    /// events are replayed as broadcast events. Note that the replayed events are recorded again when they are
+    /// dispatched by an event loop that uses this recorder.
+    pub fn replay<
+        const BACKLOG_MAX: usize,
+        const LISTENERS_MAX: usize,
+        const PRIORITIES: usize,
+        ER,
+        const STATE_SIZE: usize,
+        M,
+    >(
+        &self,
+        eventloop: &EventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX, PRIORITIES, ER, STATE_SIZE, M>,
+    ) -> usize
+    where
+        ER: Runtime,
+    {
+        let mut replayed = 0;
+        while let Some((event_box, broadcast)) = self.records.scope(|records| {
+            let record = records.get(replayed)?;
+            Some((Self::snapshot(&record.snapshot), record.broadcast))
+        }) {
+            if eventloop.send_box(event_box, broadcast).is_err() {
+                break;
+            }
+            replayed += 1;
+        }
+        replayed
+    }
+
+    /// The amount of recorded events
+    pub fn len(&self) -> usize {
+        self.records.scope(|records| records.len())
+    }
+    /// Whether no events have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.records.scope(|records| records.is_empty())
+    }
+    /// Removes all recorded events
+    pub fn clear(&self) {
+        // Snapshots have no destructor, so they can be dropped within the threadsafe scope
+        self.records.scope(|records| while records.pop().is_some() {});
+    }
+
+    /// Creates a snapshot of `event_box`
+    fn snapshot(event_box: &Box<STACKBOX_SIZE>) -> Box<STACKBOX_SIZE> {
+        // Only boxes of allowed `Copy` types are snapshotted
+        unsafe { event_box.copy_unchecked() }
+    }
+}
+impl<const STACKBOX_SIZE: usize, const DEPTH: usize, const TYPES_MAX: usize, R> RecordEvent<STACKBOX_SIZE>
+    for Recorder<STACKBOX_SIZE, DEPTH, TYPES_MAX, R>
+where
+    R: Runtime,
+{
+    fn record(&self, event_box: &Box<STACKBOX_SIZE>, broadcast: bool) {
+        // Only record allowed plain old data types
+        let type_id = event_box.inner_type_id();
+        if !self.types.scope(|types| types.iter().any(|allowed| *allowed == type_id)) {
+            return;
+        }
+
+        // Snapshots have no destructor, so the evicted snapshot can be dropped within the threadsafe scope
+        let record = Record { snapshot: Self::snapshot(event_box), broadcast };
+        self.records.scope(|records| records.push_overwrite(record));
+    }
+}
+impl<const STACKBOX_SIZE: usize, const DEPTH: usize, const TYPES_MAX: usize, R> Debug
+    for Recorder<STACKBOX_SIZE, DEPTH, TYPES_MAX, R>
+where
+    R: Runtime,
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Recorder").field("records", &self.records).field("types", &self.types).finish()
+    }
+}
+impl<const STACKBOX_SIZE: usize, const DEPTH: usize, const TYPES_MAX: usize, R> Default
+    for Recorder<STACKBOX_SIZE, DEPTH, TYPES_MAX, R>
+where
+    R: Runtime,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,10 +1,46 @@
-//! Defines required runtime-specific function stubs
+//! Defines the runtime interface and the required runtime-specific function stubs
 
-extern "Rust" {
+/// A runtime that provides the platform-specific primitives for the event loop
+///
+/// # Note
+/// The functions are associated functions without a receiver, so that the runtime can be selected as a type parameter
+/// and event loops can still be created within `const` contexts (e.g. `static`s).
+pub trait Runtime {
     /// Blocks until an event occurs (may wake spuriously); should be similar to ARM's `wfe` instruction
-    pub(crate) fn _runtime_waitforevent_r3iRR3iR();
+    ///
+    /// Raised events must be latched like ARM's event register: if an event has been raised since the last call
+    /// returned, the call must return immediately. Otherwise, an event raised between the last backlog check and this
+    /// call would get lost.
+    fn wait_for_event();
     /// Raises an event; should be similar to ARM's `sev` instruction
-    pub(crate) fn _runtime_sendevent_ZMWrWpGO();
+    fn send_event();
     /// Ensures that `code` is run exclusively, without being subject to race conditions or interrupts
-    pub(crate) fn _runtime_threadsafe_NfpNM21J(code: &mut dyn FnMut());
+    ///
+    /// Calls may be nested, i.e. `code` may call this function again.
+    fn with_critical_section(code: &mut dyn FnMut());
+}
+
+/// The default runtime, which forwards to the runtime-specific function stubs that are provided by the linked runtime
+/// crate (e.g. `embedded-eventloop-cortex-m`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct LinkedRuntime;
+impl Runtime for LinkedRuntime {
+    fn wait_for_event() {
+        unsafe { _runtime_waitforevent_r3iRR3iR() }
+    }
+    fn send_event() {
+        unsafe { _runtime_sendevent_ZMWrWpGO() }
+    }
+    fn with_critical_section(code: &mut dyn FnMut()) {
+        unsafe { _runtime_threadsafe_NfpNM21J(code) }
+    }
+}
+
+extern "Rust" {
+    /// Implements [`Runtime::wait_for_event`] for [`LinkedRuntime`]
+    fn _runtime_waitforevent_r3iRR3iR();
+    /// Implements [`Runtime::send_event`] for [`LinkedRuntime`]
+    fn _runtime_sendevent_ZMWrWpGO();
+    /// Implements [`Runtime::with_critical_section`] for [`LinkedRuntime`]
+    fn _runtime_threadsafe_NfpNM21J(code: &mut dyn FnMut());
 }
@@ -0,0 +1,58 @@
+//! A scoped single-consumer event queue for events that borrow from the stack
+
+use crate::{
+    collections::RingBuf,
+    runtime::{LinkedRuntime, Runtime},
+    threadsafe::ThreadSafeCell,
+};
+
+/// A scoped single-consumer event queue for events that are not `'static`, e.g. because they borrow a buffer from the
+/// stack
+///
+/// # Limitations
+/// Unlike [`EventLoop`](crate::EventLoop), this is not a general event loop: events are identified via their `TypeId`,
+/// which is only available for `'static` types, so there is no typed listener registration. Instead, the queue handles
+/// a single event type `T` that is passed to the single listener given to [`Self::scope`]; to handle different kinds of
+/// events, `T` can be an enum that the listener matches on. There are no listener chains, priorities or hooks, and the
+/// queue cannot be entered; it is drained once when the scope returns.
+///
+/// # Note on lifetimes
+/// A scoped event queue only exists within [`Self::scope`], and all events are dispatched before the scope returns.
+/// This ensures that borrowed events cannot outlive the data they borrow from.
+#[derive(Debug)]
+pub struct ScopedEventLoop<T, const BACKLOG_MAX: usize = 32, R: Runtime = LinkedRuntime> {
+    /// The event buffer
+    events: ThreadSafeCell<RingBuf<T, BACKLOG_MAX>, R>,
+}
+impl<T, const BACKLOG_MAX: usize, R> ScopedEventLoop<T, BACKLOG_MAX, R>
+where
+    R: Runtime,
+{
+    /// Creates a new scoped event queue and calls `scope` with it, then dispatches all sent events to `listener` until
+    /// the backlog is empty, including events that are sent by the listener itself
+    pub fn scope<L, F, FR>(mut listener: L, scope: F) -> FR
+    where
+        L: FnMut(&Self, T),
+        F: FnOnce(&Self) -> FR,
+    {
+        // Create the event queue and send the events
+        let eventloop = Self { events: ThreadSafeCell::with_runtime(RingBuf::new()) };
+        let result = scope(&eventloop);
+
+        // Process events until the backlog is drained
+        while let Some(event) = eventloop.events.scope(|events| events.pop()) {
+            listener(&eventloop, event);
+        }
+        result
+    }
+
+    /// Sends an event to the event queue, returns `Err(event)` if the backlog is reached
+    pub fn send(&self, event: T) -> Result<(), T> {
+        self.events.scope(|events| events.push(event))
+    }
+
+    /// The amount of pending events in the backlog
+    pub fn backlog_len(&self) -> usize {
+        self.events.scope(|events| events.len())
+    }
+}
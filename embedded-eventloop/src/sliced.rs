@@ -0,0 +1,192 @@
+//! An event loop that is backed by caller-provided storage, so that its capacities are runtime values
+
+use crate::{
+    boxes::Box,
+    collections::SliceRingBuf,
+    eventloop::{
+        caller, dispatch_chain, find_listener, fn_caller, EventListener, FnCallback, ListenError, ListenerId, Sendable,
+        Shared,
+    },
+    runtime::{LinkedRuntime, Runtime},
+    threadsafe::ThreadSafeCell,
+};
+use core::{any::TypeId, marker::PhantomData, mem};
+
+/// An event loop that stores its backlog and its listeners in caller-provided slices
+///
+/// # Note on capacities
+/// The backlog capacity and the amount of listener slots are the lengths of the provided slices, so they can be chosen
+/// at runtime, e.g. from a board configuration. Use [`EventLoop`](crate::EventLoop) if the capacities are known at
+/// compile time; it stores everything inline and offers the full feature set.
+///
+/// # Note on features
+/// The slice-backed event loop only supports the basic listener chain via [`Self::listen`] and [`Self::listen_fn`];
+/// priorities, deferred events, observers, hooks and metrics are not available.
+///
+/// # Note on `Send`
+/// The thread model `M` works like the one of [`EventLoop`](crate::EventLoop): the default [`Shared`] requires all
+/// events and listener states to be `Send`, while [`Local`](crate::Local) accepts values that are not `Send`, but makes
+/// the event loop neither `Send` nor `Sync`.
+#[derive(Debug)]
+pub struct SliceEventLoop<const SIZE: usize = 64, R: Runtime = LinkedRuntime, M = Shared> {
+    /// The event backlog
+    events: ThreadSafeCell<SliceRingBuf<'static, Box<SIZE>>, R>,
+    /// The registered event listeners
+    listeners: ThreadSafeCell<&'static mut [Option<EventListener<SIZE>>], R>,
+    /// The generation for the next listener
+    generation: ThreadSafeCell<usize, R>,
+    /// The thread model
+    thread_model: PhantomData<M>,
+}
+impl<const SIZE: usize, R, M> SliceEventLoop<SIZE, R, M>
+where
+    R: Runtime,
+{
+    /// Creates a new event loop that uses `backlog` as event backlog and `listeners` as listener slots; elements that
+    /// are already in the slices are dropped
+    pub fn new(
+        backlog: &'static mut [Option<Box<SIZE>>],
+        listeners: &'static mut [Option<EventListener<SIZE>>],
+    ) -> Self {
+        listeners.iter_mut().for_each(|listener| *listener = None);
+        Self {
+            events: ThreadSafeCell::with_runtime(SliceRingBuf::new(backlog)),
+            listeners: ThreadSafeCell::with_runtime(listeners),
+            generation: ThreadSafeCell::with_runtime(0),
+            thread_model: PhantomData,
+        }
+    }
+
+    /// Adds a listener to the event loop which receives all events of type `T`, and returns a handle to remove the
+    /// listener again; returns [`ListenError::Full`] if all listener slots are in use
+    ///
+    /// # Note on multiple listeners
+    /// Listeners are chained like the listeners of [`EventLoop::listen`](crate::EventLoop::listen).
+    pub fn listen<T>(&self, callback: fn(T) -> Option<T>) -> Result<ListenerId, ListenError<FnCallback<T>>>
+    where
+        T: Sendable<M>,
+    {
+        self.insert_listener::<T, _>(callback, fn_caller::<T, SIZE, SIZE>)
+    }
+    /// Adds a stateful listener to the event loop which receives all events of type `T`, and returns a handle to remove
+    /// the listener again; returns [`ListenError::TooLarge`] if the closure is larger than `SIZE`, or
+    /// [`ListenError::Full`] if all listener slots are in use
+    pub fn listen_fn<T, F>(&self, callback: F) -> Result<ListenerId, ListenError<F>>
+    where
+        T: Sendable<M>,
+        F: FnMut(T) -> Option<T> + Sendable<M>,
+    {
+        self.insert_listener::<T, F>(callback, caller::<T, F, SIZE, SIZE>)
+    }
+    /// Removes a listener from the event loop, returns `false` if the listener has already been removed
+    pub fn unlisten(&self, id: ListenerId) -> bool {
+        // Ensure the slot still contains the listener and has not been reused; the listener is dropped outside of the
+        // threadsafe scope
+        let removed = self.listeners.scope(|listeners| {
+            let index = find_listener(listeners, id.generation, id.index)?;
+            listeners[index].take()
+        });
+        removed.is_some()
+    }
+
+    /// Sends an event to the event loop, returns `Err(event)` if the event is too large or the backlog is full
+    pub fn send<T>(&self, event: T) -> Result<(), T>
+    where
+        T: Sendable<M>,
+    {
+        // Box and insert the event
+        let event_box = Box::new(event)?;
+        let result = self.events.scope(|events| events.push(event_box));
+        result.map_err(|event_box| event_box.into_inner().expect("failed to unwrap event"))?;
+
+        // Trigger a hardware event; this must happen after the event is enqueued to not race with `enter`
+        R::send_event();
+        Ok(())
+    }
+
+    /// Processes at most one pending event without waiting, returns `false` if there was no pending event
+    pub fn poll(&self) -> bool {
+        let Some(event_box) = self.events.scope(|events| events.pop()) else {
+            return false;
+        };
+        self.dispatch(event_box);
+        true
+    }
+    /// Processes pending events until the backlog is empty, including events that are sent during processing
+    pub fn run_until_idle(&self) {
+        // Process events until the backlog is drained
+        while self.poll() {}
+    }
+    /// Enters the event loop
+    pub fn enter(&self) -> ! {
+        loop {
+            // Process all pending events, then wait for the next hardware event
+            self.run_until_idle();
+            R::wait_for_event();
+        }
+    }
+
+    /// The amount of pending events in the backlog
+    pub fn backlog_len(&self) -> usize {
+        self.events.scope(|events| events.len())
+    }
+    /// The amount of registered listeners
+    pub fn listeners_len(&self) -> usize {
+        self.listeners.scope(|listeners| listeners.iter().flatten().count())
+    }
+    /// The maximum amount of pending events
+    pub fn backlog_capacity(&self) -> usize {
+        self.events.scope(|events| events.capacity())
+    }
+    /// The maximum amount of listeners
+    pub fn listeners_capacity(&self) -> usize {
+        self.listeners.scope(|listeners| listeners.len())
+    }
+
+    /// Boxes a callback and inserts it into the first free listener slot
+    fn insert_listener<T, F>(
+        &self,
+        callback: F,
+        caller: fn(Box<SIZE>, Box<SIZE>) -> (Option<Box<SIZE>>, Box<SIZE>),
+    ) -> Result<ListenerId, ListenError<F>>
+    where
+        T: 'static,
+        F: 'static,
+    {
+        // Box the callback
+        let callback_box =
+            Box::new(callback).map_err(|callback| ListenError::TooLarge { callback, available: SIZE })?;
+        let generation = self.generation.scope(|generation| mem::replace(generation, generation.wrapping_add(1)));
+        let listener = EventListener {
+            type_id: TypeId::of::<T>(),
+            generation,
+            once: false,
+            observer: false,
+            callback_box: Some(callback_box),
+            caller,
+        };
+
+        // Insert the listener into the first free slot
+        let inserted = self.listeners.scope(|listeners| match listeners.iter().position(Option::is_none) {
+            Some(index) => {
+                listeners[index] = Some(listener);
+                Ok(index)
+            }
+            None => Err((listener, listeners.len())),
+        });
+        match inserted {
+            Ok(index) => Ok(ListenerId { index, generation }),
+            Err((listener, capacity)) => {
+                let callback_box = listener.callback_box.expect("missing callback");
+                let callback = callback_box.into_inner().expect("failed to unwrap callback");
+                Err(ListenError::Full { callback, capacity })
+            }
+        }
+    }
+
+    /// Passes an event along the listener chain
+    fn dispatch(&self, event_box: Box<SIZE>) {
+        // Events that are not consumed by any listener are dropped
+        let (_unhandled_event_box, _) = dispatch_chain(&self.listeners, event_box, false, false);
+    }
+}
@@ -1,25 +1,110 @@
 //! A threadsafe cell type
 
-use crate::runtime;
+use crate::runtime::{LinkedRuntime, Runtime};
 use core::{
-    cell::UnsafeCell,
+    cell::{Cell, UnsafeCell},
     fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+    mem, ptr,
 };
 
 /// A threadsafe cell
-#[repr(transparent)]
-pub struct ThreadSafeCell<T> {
+///
+/// # Note on re-entrancy
+/// Scopes on different cells can be nested freely, since the threadsafe context is reentrant. A scope on a cell must
+/// however not re-enter a scope on the same cell (e.g. via a callback that is invoked within the scope), since this
+/// would create two aliasing mutable references to the value. Such a re-entrant access panics in [`Self::scope`], and
+/// returns `None` in [`Self::try_scope`].
+///
+/// # Note on `Debug`
+/// Formatting a cell uses [`Self::try_scope`], so it never panics and never nests a second borrow of the value: if the
+/// value is already borrowed by an outer scope on this cell (e.g. when logging from within a scope), the cell is
+/// formatted as `ThreadSafe { <locked> }` instead. The threadsafe context is still entered briefly to read the value.
+///
+/// # Note on portability
+/// The borrow flag is a plain [`Cell`] that is only accessed within the threadsafe context, so the cell does not rely
+/// on atomic operations and also works on targets without compare-and-swap support (e.g. Cortex-M0/M0+).
+pub struct ThreadSafeCell<T, R = LinkedRuntime> {
     /// The wrapped value
     inner: UnsafeCell<T>,
+    /// Whether the value is currently borrowed by a scope
+    borrowed: Cell<bool>,
+    /// The runtime that provides the threadsafe context
+    _runtime: PhantomData<fn() -> R>,
 }
 impl<T> ThreadSafeCell<T> {
     /// Creates a new threadsafe Cell
     pub const fn new(value: T) -> Self {
-        Self { inner: UnsafeCell::new(value) }
+        Self::with_runtime(value)
+    }
+}
+impl<T, R> ThreadSafeCell<T, R>
+where
+    R: Runtime,
+{
+    /// Creates a new threadsafe Cell that uses the runtime `R` to provide the threadsafe context
+    pub const fn with_runtime(value: T) -> Self {
+        Self { inner: UnsafeCell::new(value), borrowed: Cell::new(false), _runtime: PhantomData }
     }
 
     /// Provides scoped access to the underlying value
+    ///
+    /// # Panics
+    /// This function panics if the value is already borrowed by an outer scope on this cell (e.g. if a scope on this
+    /// cell indirectly calls back into this method).
     pub fn scope<F, FR>(&self, scope: F) -> FR
+    where
+        F: FnOnce(&mut T) -> FR,
+    {
+        let result = self.scope_impl(scope);
+        result.expect("re-entrant access to value that is already borrowed by an outer scope")
+    }
+    /// Provides scoped access to the underlying value, returns `None` if the value is already borrowed by an outer
+    /// scope on this cell (e.g. if a scope on this cell indirectly calls back into this method)
+    pub fn try_scope<F, FR>(&self, scope: F) -> Option<FR>
+    where
+        F: FnOnce(&mut T) -> FR,
+    {
+        self.scope_impl(scope)
+    }
+
+    /// Replaces the underlying value with `value` and returns the previous value
+    pub fn replace(&self, value: T) -> T {
+        self.scope(|inner| mem::replace(inner, value))
+    }
+    /// Takes the underlying value and leaves `T::default()` in its place
+    pub fn take(&self) -> T
+    where
+        T: Default,
+    {
+        self.replace(T::default())
+    }
+    /// Swaps the underlying values of `self` and `other` within a single threadsafe context
+    ///
+    /// # Note on lock ordering
+    /// The threadsafe context is a single, global and reentrant exclusive section (e.g. a critical section). Both cells
+    /// are accessed within the same section, so there is no lock ordering between cells that could deadlock.
+    pub fn swap(&self, other: &Self) {
+        // Swapping a cell with itself is a no-op, and would otherwise borrow the value twice
+        if ptr::eq(self, other) {
+            return;
+        }
+        scope2(self, other, mem::swap);
+    }
+
+    /// Gets a mutable reference to the underlying value without entering a threadsafe context
+    ///
+    /// This is zero-cost, since the exclusive reference to the cell already guarantees exclusive access to the value.
+    pub const fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+    /// Consumes the cell and returns the underlying value without entering a threadsafe context
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+
+    /// Provides scoped access to the underlying value, and refuses the access if the value is already borrowed
+    fn scope_impl<F, FR>(&self, scope: F) -> Option<FR>
     where
         F: FnOnce(&mut T) -> FR,
     {
@@ -27,33 +112,87 @@ impl<T> ThreadSafeCell<T> {
         let mut scope = Some(scope);
         let mut result: Option<FR> = None;
 
-        // Create the caller
-        let mut call_scope = || {
-            // Get the value
-            let value_ptr = self.inner.get();
-            let value = unsafe { value_ptr.as_mut() }.expect("unexpected NULL pointer inside cell");
-
-            // Take the scope
+        // Run the implementation in a threadsafe context and return the result
+        R::with_critical_section(&mut || {
             let scope = scope.take().expect("missing scope function");
-            result = Some(scope(value));
-        };
+            result = self.borrow_impl(scope);
+        });
+        result
+    }
+    /// Provides access to the underlying value, and refuses the access if the value is already borrowed
+    ///
+    /// This function must only be called within the threadsafe context.
+    fn borrow_impl<F, FR>(&self, scope: F) -> Option<FR>
+    where
+        F: FnOnce(&mut T) -> FR,
+    {
+        /// Restores the previous borrow state, even if the scope panics
+        struct Borrow<'a> {
+            /// The borrow flag
+            flag: &'a Cell<bool>,
+            /// The previous borrow state
+            previous: bool,
+        }
+        impl Drop for Borrow<'_> {
+            fn drop(&mut self) {
+                self.flag.set(self.previous);
+            }
+        }
 
-        // Run the implementation in a threadsafe context and return the result
-        unsafe { runtime::_runtime_threadsafe_NfpNM21J(&mut call_scope) };
-        result.expect("implementation scope did not set result value")
+        // Mark the value as borrowed; the flag is only accessed within the threadsafe context
+        let previous = self.borrowed.replace(true);
+        let _borrow = Borrow { flag: &self.borrowed, previous };
+        if previous {
+            return None;
+        }
+
+        // Get the value and call the scope
+        let value_ptr = self.inner.get();
+        let value = unsafe { value_ptr.as_mut() }.expect("unexpected NULL pointer inside cell");
+        Some(scope(value))
     }
 }
-impl<T> Debug for ThreadSafeCell<T>
+
+/// Provides scoped access to the underlying values of two different cells within a single threadsafe context, e.g. to
+/// move a value from one cell into another atomically
+///
+/// # Panics
+/// This function panics if `a` and `b` are the same cell, or if any of the values is already borrowed by an outer
+/// scope (see [`ThreadSafeCell::scope`]).
+pub fn scope2<A, B, R, F, FR>(a: &ThreadSafeCell<A, R>, b: &ThreadSafeCell<B, R>, scope: F) -> FR
+where
+    R: Runtime,
+    F: FnOnce(&mut A, &mut B) -> FR,
+{
+    debug_assert!(!ptr::addr_eq(a, b), "cannot borrow the same cell twice");
+
+    // Create mutable slots to transfer state to/from the closure
+    let mut scope = Some(scope);
+    let mut result: Option<FR> = None;
+
+    // Borrow both values within the same threadsafe context
+    R::with_critical_section(&mut || {
+        let scope = scope.take().expect("missing scope function");
+        result = a.borrow_impl(|a| b.borrow_impl(|b| scope(a, b))).flatten();
+    });
+    result.expect("re-entrant access to value that is already borrowed by an outer scope")
+}
+
+impl<T, R> Debug for ThreadSafeCell<T, R>
 where
     T: Debug,
+    R: Runtime,
 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        self.scope(|value| f.debug_struct("ThreadSafe").field("value", value).finish())
+        // Do not panic if the value is borrowed by an outer scope, e.g. if the cell is logged from within a scope
+        let result = self.try_scope(|value| f.debug_struct("ThreadSafe").field("value", value).finish());
+        result.unwrap_or_else(|| f.write_str("ThreadSafe { <locked> }"))
     }
 }
-unsafe impl<T> Sync for ThreadSafeCell<T>
+unsafe impl<T, R> Sync for ThreadSafeCell<T, R>
 where
     T: Send,
+    R: Runtime,
 {
     // Marker trait, no members to implement
 }
@@ -0,0 +1,113 @@
+//! A timer wheel that turns the ticks of an existing tick interrupt into timeout events
+
+use crate::{
+    collections::Stack,
+    runtime::{LinkedRuntime, Runtime},
+    threadsafe::ThreadSafeCell,
+    EventLoop,
+};
+
+/// A timeout event that is sent once the deadline of a scheduled timer has passed
+#[derive(Debug, Clone, Copy)]
+pub struct Timeout {
+    /// The deadline in ticks
+    pub deadline_ticks: u64,
+    /// The callback of the timer
+    pub callback: fn(),
+}
+impl Timeout {
+    /// Calls the callback of the timer
+    pub fn fire(self) {
+        (self.callback)()
+    }
+}
+
+/// A timer wheel with up to `SLOTS` concurrently scheduled timers
+///
+/// # Note on ticks
+/// The timer wheel does not access any hardware and has no notion of time by itself. Instead, it is driven by the
+/// caller's existing tick interrupt (e.g. SysTick), which passes the current tick count to [`Self::advance`]. Once the
+/// deadline of a timer has passed, a [`Timeout`] event is sent to the event loop; the timeout's callback can then be
+/// called by a listener, e.g. via `eventloop.listen(|timeout: Timeout| { timeout.fire(); None })`.
+#[derive(Debug)]
+pub struct TimerWheel<const SLOTS: usize = 8, R: Runtime = LinkedRuntime> {
+    /// The scheduled timers
+    timers: ThreadSafeCell<Stack<Timeout, SLOTS>, R>,
+}
+impl<const SLOTS: usize, R> TimerWheel<SLOTS, R>
+where
+    R: Runtime,
+{
+    /// Creates a new timer wheel
+    pub const fn new() -> Self {
+        Self { timers: ThreadSafeCell::with_runtime(Stack::new()) }
+    }
+
+    /// Schedules `callback` to be sent as [`Timeout`] event once `deadline_ticks` has passed, returns `Err(callback)`
+    /// if `SLOTS` timers are already scheduled
+    pub fn schedule(&self, deadline_ticks: u64, callback: fn()) -> Result<(), fn()> {
+        let timeout = Timeout { deadline_ticks, callback };
+        self.timers.scope(|timers| timers.push(timeout)).map_err(|timeout| timeout.callback)
+    }
+
+    /// Sends a [`Timeout`] event for every timer whose deadline is less than or equal to `now_ticks` to `eventloop`,
+    /// earliest deadline first, and returns the amount of sent events
+    ///
+    /// Timers that do not fit into the backlog stay scheduled and are sent by a subsequent call.
+    pub fn advance<
+        const STACKBOX_SIZE: usize,
+        const BACKLOG_MAX: usize,
+        const LISTENERS_MAX: usize,
+        const PRIORITIES: usize,
+        ER,
+        const STATE_SIZE: usize,
+    >(
+        &self,
+        now_ticks: u64,
+        eventloop: &EventLoop<STACKBOX_SIZE, BACKLOG_MAX, LISTENERS_MAX, PRIORITIES, ER, STATE_SIZE>,
+    ) -> usize
+    where
+        ER: Runtime,
+    {
+        self.timers.scope(|timers| {
+            let mut sent = 0;
+            while let Some((index, timeout)) = Self::next_due(timers, now_ticks) {
+                // Send the timeout and remove the timer afterwards, so that it stays scheduled if the backlog is full
+                if eventloop.send(timeout).is_err() {
+                    break;
+                }
+                timers.swap_remove(index);
+                sent += 1;
+            }
+            sent
+        })
+    }
+
+    /// Removes all scheduled timers
+    pub fn clear(&self) {
+        self.timers.scope(|timers| timers.clear());
+    }
+
+    /// The amount of scheduled timers
+    pub fn len(&self) -> usize {
+        self.timers.scope(|timers| timers.len())
+    }
+    /// Whether no timers are scheduled
+    pub fn is_empty(&self) -> bool {
+        self.timers.scope(|timers| timers.is_empty())
+    }
+
+    /// Finds the due timer with the earliest deadline and returns its index and a copy of it
+    fn next_due(timers: &Stack<Timeout, SLOTS>, now_ticks: u64) -> Option<(usize, Timeout)> {
+        let due = timers.iter().copied().enumerate().filter(|(_, timeout)| timeout.deadline_ticks <= now_ticks);
+        due.min_by_key(|(_, timeout)| timeout.deadline_ticks)
+    }
+}
+impl<const SLOTS: usize, R> Default for TimerWheel<SLOTS, R>
+where
+    R: Runtime,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
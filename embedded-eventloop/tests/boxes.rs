@@ -1,7 +1,11 @@
 //! A box
 
-use eventloop::boxes::{Box, CopyBox};
-use std::rc::Rc;
+use embedded_eventloop::boxes::{Box, BoxError, CopyBox, DynBox};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    ptr,
+    rc::Rc,
+};
 
 #[test]
 fn box_simple() {
@@ -24,10 +28,10 @@ fn copybox_simple() {
     // Box and unbox value
     let value: fn(usize) -> usize = square;
     let boxed = CopyBox::<128>::new(value).expect("failed to box simple value");
-    let unboxed = boxed.inner().expect("failed to unbox simple value");
+    let unboxed: fn(usize) -> usize = boxed.inner().expect("failed to unbox simple value");
 
     // Compare values
-    assert_eq!(value, unboxed, "invalid unboxed value");
+    assert!(ptr::fn_addr_eq(value, unboxed), "invalid unboxed value");
     assert_eq!(unboxed(7), 49, "invalid function result");
 }
 
@@ -69,6 +73,22 @@ fn box_constraints_size() {
     assert!(Box::<7>::new(value).is_err(), "unexpected success when boxing too large value");
 }
 
+#[test]
+fn box_try_new() {
+    // Box a value that fits and a value that is too large
+    let boxed = Box::<8>::try_new(7u64).expect("failed to box value");
+    assert_eq!(boxed.into_inner::<u64>().ok(), Some(7), "invalid unboxed value");
+    let error = Box::<7>::try_new(7u64).expect_err("unexpected success when boxing too large value");
+    assert_eq!(error, BoxError { needed: 8, available: 7 }, "invalid box error");
+}
+
+#[test]
+fn box_new_checked() {
+    // Box a value that is statically known to fit
+    let boxed = Box::<8>::new_checked(7u64);
+    assert_eq!(boxed.into_inner::<u64>().ok(), Some(7), "invalid unboxed value");
+}
+
 #[test]
 fn copybox_constraints_size() {
     // Create a value that is too large
@@ -95,3 +115,251 @@ fn copybox_constraints_type() {
     // Try to unbox it as i64
     assert!(boxed.inner::<i64>().is_none(), "unexpected success when unboxing u64-typed value as i64");
 }
+
+#[test]
+fn box_is() {
+    // Check the type of boxed values
+    let boxed = Box::<128>::new(7u64).expect("failed to box u64-typed value");
+    assert!(boxed.is::<u64>(), "invalid type of boxed value");
+    assert!(!boxed.is::<i64>(), "u64-typed value is reported as i64");
+    let boxed = CopyBox::<128>::new(7u64).expect("failed to box u64-typed value");
+    assert!(boxed.is::<u64>(), "invalid type of boxed value");
+    assert!(!boxed.is::<i64>(), "u64-typed value is reported as i64");
+}
+
+#[test]
+fn box_as_ref() {
+    // Box a value and access it in-place
+    let mut boxed = Box::<128>::new(*b"Testolope").expect("failed to box simple value");
+    assert_eq!(boxed.as_ref::<[u8; 9]>(), Some(b"Testolope"), "invalid referenced value");
+
+    // Modify the value in-place
+    let value: &mut [u8; 9] = boxed.as_mut().expect("failed to reference boxed value");
+    value[0] = b'N';
+    let unboxed: [u8; 9] = boxed.into_inner().expect("failed to unbox simple value");
+    assert_eq!(&unboxed, b"Nestolope", "invalid modified value");
+}
+
+#[test]
+fn box_as_ref_constraints_type() {
+    // Box an u8
+    let mut boxed = Box::<128>::new(7u8).expect("failed to box u8-typed value");
+
+    // Try to reference it as i8
+    assert!(boxed.as_ref::<i8>().is_none(), "unexpected success when referencing u8-typed value as i8");
+    assert!(boxed.as_mut::<i8>().is_none(), "unexpected success when referencing u8-typed value as i8");
+}
+
+#[test]
+fn box_as_ref_aligned() {
+    // Box over-aligned values and access them in-place
+    let mut boxed = Box::<128>::new(7u128).expect("failed to box u128-typed value");
+    assert_eq!(boxed.as_ref::<u128>(), Some(&7), "invalid referenced value");
+    *boxed.as_mut::<u128>().expect("failed to reference boxed value") = 4;
+    assert_eq!(boxed.into_inner::<u128>().ok(), Some(4), "invalid modified value");
+
+    // Ensure the storage is aligned even if the box itself is moved around
+    let boxes = [(0u8, Box::<24>::new(1u64)), (0u8, Box::<24>::new(2u64))];
+    for (_, boxed) in boxes {
+        let boxed = boxed.expect("failed to box u64-typed value");
+        assert!(boxed.as_ref::<u64>().is_some(), "failed to reference boxed value");
+    }
+}
+
+#[test]
+#[cfg(feature = "type-name")]
+fn box_type_name() {
+    // Box values and validate the type names
+    let boxed = Box::<128>::new(7u64).expect("failed to box u64-typed value");
+    assert_eq!(boxed.type_name(), "u64", "invalid type name");
+    let boxed = CopyBox::<128>::new((7u8, 4i16)).expect("failed to box tuple-typed value");
+    assert_eq!(boxed.type_name(), "(u8, i16)", "invalid type name");
+}
+
+#[test]
+fn copybox_eq() {
+    /// Hashes a value
+    fn hash<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // Compare equal values
+    let boxed = CopyBox::<128>::new_comparable(7u32).expect("failed to box u32-typed value");
+    let other = CopyBox::<128>::new_comparable(7u32).expect("failed to box u32-typed value");
+    assert_eq!(boxed, other, "equal values are not equal");
+    assert_eq!(hash(&boxed), hash(&other), "equal values have different hashes");
+
+    // Compare different values and different types with the same bytes
+    let value = CopyBox::<128>::new_comparable(4u32).expect("failed to box u32-typed value");
+    let typed = CopyBox::<128>::new_comparable(7i32).expect("failed to box i32-typed value");
+    assert_ne!(boxed, value, "different values are equal");
+    assert_ne!(boxed, typed, "different types are equal");
+
+    // Compare values with padding bytes
+    let padded = CopyBox::<128>::new_comparable((7u8, 4u32)).expect("failed to box tuple-typed value");
+    let other = CopyBox::<128>::new_comparable((7u8, 4u32)).expect("failed to box tuple-typed value");
+    assert_eq!(padded, other, "equal padded values are not equal");
+    assert_eq!(hash(&padded), hash(&other), "equal padded values have different hashes");
+
+    // Compare boxes that are not comparable by value
+    let plain = CopyBox::<128>::new(7u32).expect("failed to box u32-typed value");
+    let copied = plain;
+    assert!(!plain.is_comparable(), "plain box is comparable");
+    assert_eq!(plain, plain, "box is not equal to itself");
+    assert_ne!(plain, copied, "copies of a plain box are equal");
+    assert_ne!(plain, boxed, "plain box is equal to comparable box");
+}
+
+#[test]
+fn box_try_clone() {
+    // Box a reference-counted value as cloneable and clone the box
+    let rc = Rc::new(7u64);
+    let boxed = Box::<128>::new_cloneable(Rc::clone(&rc)).expect("failed to box cloneable value");
+    let cloned = boxed.try_clone().expect("failed to clone cloneable box");
+    assert_eq!(Rc::strong_count(&rc), 3, "invalid reference count");
+
+    // Unbox the clone and drop the original box
+    let unboxed: Rc<u64> = cloned.into_inner().expect("failed to unbox cloned value");
+    assert_eq!(*unboxed, 7, "invalid cloned value");
+    drop(boxed);
+    assert_eq!(Rc::strong_count(&rc), 2, "invalid reference count");
+}
+
+#[test]
+fn box_try_clone_constraints() {
+    // Boxes created the non-cloneable way cannot be cloned
+    let boxed = Box::<128>::new(7u64).expect("failed to box value");
+    assert!(boxed.try_clone().is_none(), "cloned non-cloneable box");
+}
+
+#[test]
+fn dynbox_as_dyn() {
+    /// A shape with an area
+    trait Shape {
+        /// The area of the shape
+        fn area(&self) -> u64;
+    }
+    /// A square
+    #[derive(Debug)]
+    struct Square(u64);
+    impl Shape for Square {
+        fn area(&self) -> u64 {
+            self.0 * self.0
+        }
+    }
+    /// A rectangle that holds a reference-counted value to validate drops
+    #[derive(Debug)]
+    struct Rect {
+        width: u32,
+        height: u32,
+        _rc: Rc<()>,
+    }
+    impl Shape for Rect {
+        fn area(&self) -> u64 {
+            u64::from(self.width) * u64::from(self.height)
+        }
+    }
+
+    // Box heterogeneous values and access them as trait objects
+    let rc = Rc::new(());
+    let square = DynBox::<dyn Shape, 32>::new(Square(3), |value| value).expect("failed to box square");
+    let rect = DynBox::<dyn Shape, 32>::new(Rect { width: 2, height: 5, _rc: Rc::clone(&rc) }, |value| value)
+        .expect("failed to box rect");
+    let area: u64 = [&square, &rect].iter().map(|shape| shape.as_dyn().area()).sum();
+    assert_eq!(area, 19, "invalid accumulated area");
+
+    // Unwrap the square and drop the rectangle
+    let square: Square = square.into_inner().expect("failed to unbox square");
+    assert_eq!(square.0, 3, "invalid unboxed value");
+    drop(rect);
+    assert_eq!(Rc::strong_count(&rc), 1, "invalid reference count");
+}
+
+#[test]
+fn box_map() {
+    // Map the value to a different type
+    let boxed = Box::<8>::new(7u8).expect("failed to box value");
+    let mapped = boxed.map(|value: u8| u64::from(value) * 2).expect("failed to map value");
+    assert_eq!(mapped.into_inner::<u64>().ok(), Some(14), "invalid mapped value");
+
+    // Map with a wrong source type or a too large target type
+    let boxed = Box::<8>::new(7u8).expect("failed to box value");
+    let boxed = boxed.map(|value: u16| value).expect_err("unexpected success when mapping wrong type");
+    let boxed = boxed.map(|value: u8| [value; 9]).expect_err("unexpected success when mapping too large type");
+    assert_eq!(boxed.into_inner::<u8>().ok(), Some(7), "invalid unmapped value");
+}
+
+#[test]
+fn box_zero_sized() {
+    /// A marker event
+    #[derive(Debug, PartialEq, Eq)]
+    struct Tick;
+
+    // Box the zero-sized values into a zero-sized box
+    let tick = Box::<0>::new(Tick).expect("failed to box marker value");
+    let unit = Box::<0>::new(()).expect("failed to box unit value");
+    assert_ne!(tick.inner_type_id(), unit.inner_type_id(), "type IDs of zero-sized values are not distinct");
+
+    // Ensure that the types are still distinguished, and unbox the values
+    let tick = tick.into_inner::<()>().expect_err("unexpected success when unboxing marker value as unit");
+    let unit = unit.into_inner::<Tick>().expect_err("unexpected success when unboxing unit value as marker");
+    assert_eq!(tick.into_inner::<Tick>().expect("failed to unbox marker value"), Tick, "invalid marker value");
+    unit.into_inner::<()>().expect("failed to unbox unit value");
+}
+
+#[test]
+fn box_fits() {
+    // Validate the size checks, also in a const context
+    const { assert!(Box::<8>::fits::<u64>(), "u64 does not fit into 8 byte stackbox") };
+    assert!(!Box::<8>::fits::<[u8; 9]>(), "unexpected fit of oversized value");
+    assert!(Box::<0>::fits::<()>(), "zero-sized value does not fit into zero-sized stackbox");
+    assert_eq!(Box::<24>::size_of(), 24, "invalid stackbox size");
+}
+
+#[test]
+fn box_as_bytes() {
+    /// A plain old data value
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(C)]
+    struct Sample {
+        /// The timestamp
+        timestamp: u32,
+        /// The value
+        value: [u8; 4],
+    }
+
+    // Box the value and get the raw bytes
+    let sample = Sample { timestamp: 0x0403_0201, value: *b"Test" };
+    let boxed = Box::<64>::new(sample).expect("failed to box value");
+    let bytes = unsafe { boxed.as_bytes() };
+    assert_eq!(bytes.len(), 8, "invalid amount of value bytes");
+    assert_eq!(&bytes[4..], b"Test", "invalid value bytes");
+
+    // Reconstruct the box and unbox the value
+    let restored = unsafe { Box::<64>::from_bytes(boxed.inner_type_id(), bytes) }.expect("failed to restore box");
+    assert_eq!(restored.into_inner::<Sample>().expect("failed to unbox value"), sample, "invalid restored value");
+    assert!(unsafe { Box::<4>::from_bytes(boxed.inner_type_id(), bytes) }.is_none(), "unexpected restored box");
+}
+
+#[test]
+fn box_match_take() {
+    /// Routes a boxed value by its type
+    fn route(boxed: Box<8>) -> String {
+        let matcher = boxed.match_take();
+        let matcher = matcher.case(|value: u8| format!("u8: {value}")).case(|value: i64| format!("i64: {value}"));
+        // The unmatched test values have no padding bytes
+        matcher.otherwise(|boxed| format!("unknown: {}", unsafe { boxed.as_bytes() }.len()))
+    }
+
+    // Route the values
+    assert_eq!(route(Box::new(7u8).expect("failed to box value")), "u8: 7", "invalid route");
+    assert_eq!(route(Box::new(-4i64).expect("failed to box value")), "i64: -4", "invalid route");
+    assert_eq!(route(Box::new(7u16).expect("failed to box value")), "unknown: 2", "invalid route");
+
+    // Recover an unmatched box
+    let boxed: Box<8> = Box::new(7u16).expect("failed to box value");
+    let unmatched = boxed.match_take::<()>().case(|_: u8| ()).finish().expect_err("unexpected match");
+    assert_eq!(unmatched.into_inner::<u16>().ok(), Some(7), "invalid unmatched value");
+}
@@ -0,0 +1,42 @@
+//! A bounded channel
+
+use embedded_eventloop::channel::Channel;
+use embedded_eventloop_std as _;
+use std::thread;
+
+#[test]
+fn channel_try_send() {
+    // Fill the channel
+    let channel: Channel<u8, 2> = Channel::new();
+    channel.try_send(1).expect("failed to send value");
+    channel.try_send(2).expect("failed to send value");
+    assert!(channel.is_full(), "channel is not full");
+    assert_eq!(channel.try_send(3), Err(3), "unexpected success when sending into full channel");
+
+    // Drain the channel
+    assert_eq!(channel.try_recv(), Some(1), "invalid received value");
+    assert_eq!(channel.try_recv(), Some(2), "invalid received value");
+    assert_eq!(channel.try_recv(), None, "unexpected value in empty channel");
+    assert!(channel.is_empty(), "channel is not empty");
+}
+
+#[test]
+fn channel_recv() {
+    /// The channel
+    static CHANNEL: Channel<usize, 4> = Channel::new();
+
+    // Send values from another thread and receive them in order
+    let producer = thread::spawn(|| {
+        for value in 0..100 {
+            let mut value = value;
+            while let Err(rejected) = CHANNEL.try_send(value) {
+                value = rejected;
+                thread::yield_now();
+            }
+        }
+    });
+    for expected in 0..100 {
+        assert_eq!(CHANNEL.recv(), expected, "invalid received value");
+    }
+    producer.join().expect("producer thread panicked");
+}
@@ -0,0 +1,463 @@
+//! Stack-allocated collections
+
+use embedded_eventloop::collections::{RingBuf, Stack};
+use std::rc::Rc;
+
+#[test]
+fn ringbuf_len() {
+    // Fill the ring buffer
+    let mut ringbuf = RingBuf::<u8, 4>::new();
+    assert!(ringbuf.is_empty(), "new ring buffer is not empty");
+    for element in 0..4 {
+        assert_eq!(ringbuf.len(), element as usize, "invalid ring buffer length");
+        ringbuf.push(element).expect("failed to push element");
+    }
+
+    // Validate the full state where head and tail are equal
+    assert!(ringbuf.is_full(), "ring buffer is not full");
+    assert_eq!(ringbuf.len(), 4, "invalid length of full ring buffer");
+    assert!(ringbuf.push(4).is_err(), "unexpected success when pushing into full ring buffer");
+}
+
+#[test]
+fn ringbuf_len_wrapped() {
+    // Move head and tail across the wrap boundary
+    let mut ringbuf = RingBuf::<u8, 4>::new();
+    for element in 0..3 {
+        ringbuf.push(element).expect("failed to push element");
+    }
+    for _ in 0..3 {
+        ringbuf.pop().expect("failed to pop element");
+    }
+    for element in 3..6 {
+        ringbuf.push(element).expect("failed to push element");
+    }
+
+    // Validate the wrapped state
+    assert_eq!(ringbuf.len(), 3, "invalid length of wrapped ring buffer");
+    assert!(!ringbuf.is_full(), "unexpected full ring buffer");
+    ringbuf.push(6).expect("failed to push element");
+    assert!(ringbuf.is_full(), "ring buffer is not full");
+    assert_eq!(ringbuf.len(), 4, "invalid length of full wrapped ring buffer");
+}
+
+#[test]
+fn ringbuf_peek() {
+    // Peek into an empty ring buffer
+    let mut ringbuf = RingBuf::<u8, 4>::new();
+    assert_eq!(ringbuf.peek(), None, "unexpected element in empty ring buffer");
+
+    // Peek does not remove the element
+    ringbuf.push(7).expect("failed to push element");
+    ringbuf.push(4).expect("failed to push element");
+    assert_eq!(ringbuf.peek(), Some(&7), "invalid peeked element");
+    assert_eq!(ringbuf.len(), 2, "peek removed an element");
+    assert_eq!(ringbuf.pop(), Some(7), "invalid popped element");
+    assert_eq!(ringbuf.peek(), Some(&4), "invalid peeked element");
+}
+
+#[test]
+fn ringbuf_get() {
+    // Wrap the ring buffer around so that the elements span the end of the storage
+    let mut ringbuf = RingBuf::<u8, 4>::new();
+    assert_eq!(ringbuf.get(0), None, "unexpected element in empty ring buffer");
+    for element in [1, 2, 3] {
+        ringbuf.push(element).expect("failed to push element");
+    }
+    assert_eq!(ringbuf.pop(), Some(1), "invalid popped element");
+    assert_eq!(ringbuf.pop(), Some(2), "invalid popped element");
+    for element in [4, 5] {
+        ringbuf.push(element).expect("failed to push element");
+    }
+
+    // Get the elements in FIFO order
+    assert_eq!(ringbuf.get(0), Some(&3), "invalid element");
+    assert_eq!(ringbuf.get(2), Some(&5), "invalid wrapped element");
+    assert_eq!(ringbuf.get(3), None, "unexpected element beyond length");
+    assert_eq!(RingBuf::<u8, 0>::new().get(0), None, "unexpected element in zero-capacity ring buffer");
+}
+
+#[test]
+fn ringbuf_push_overwrite() {
+    // Fill the ring buffer
+    let mut ringbuf = RingBuf::<u8, 3>::new();
+    for element in 0..3 {
+        assert_eq!(ringbuf.push_overwrite(element), None, "unexpected eviction");
+    }
+
+    // Overwrite the oldest elements
+    assert_eq!(ringbuf.push_overwrite(3), Some(0), "invalid evicted element");
+    assert_eq!(ringbuf.push_overwrite(4), Some(1), "invalid evicted element");
+    assert_eq!(ringbuf.len(), 3, "invalid ring buffer length");
+
+    // Validate the order
+    assert_eq!(ringbuf.pop(), Some(2), "invalid popped element");
+    assert_eq!(ringbuf.pop(), Some(3), "invalid popped element");
+    assert_eq!(ringbuf.pop(), Some(4), "invalid popped element");
+    assert_eq!(ringbuf.pop(), None, "unexpected element in empty ring buffer");
+}
+
+#[test]
+fn stack_pop() {
+    // Fill the stack
+    let mut stack = Stack::<u8, 3>::new();
+    assert!(stack.is_empty(), "new stack is not empty");
+    for element in 0..3 {
+        stack.push(element).expect("failed to push element");
+    }
+    assert!(stack.is_full(), "stack is not full");
+    assert_eq!(stack.len(), 3, "invalid stack length");
+
+    // Pop the elements in reverse order
+    for element in (0..3).rev() {
+        assert_eq!(stack.pop(), Some(element), "invalid popped element");
+    }
+    assert_eq!(stack.pop(), None, "unexpected element in empty stack");
+    assert!(stack.is_empty(), "stack is not empty");
+}
+
+#[test]
+fn stack_drop() {
+    // Fill the stack with reference counted values
+    let rc = Rc::new(7);
+    let mut stack = Stack::<Rc<i32>, 4>::new();
+    for _ in 0..4 {
+        stack.push(Rc::clone(&rc)).expect("failed to push element");
+    }
+    assert_eq!(Rc::strong_count(&rc), 5, "invalid reference count");
+
+    // Pop and clear the stack, and ensure that each element is dropped exactly once
+    drop(stack.pop());
+    assert_eq!(Rc::strong_count(&rc), 4, "invalid reference count");
+    stack.clear();
+    assert_eq!(Rc::strong_count(&rc), 1, "invalid reference count");
+    assert!(stack.is_empty(), "stack is not empty");
+
+    // Refill the stack and drop it
+    stack.push(Rc::clone(&rc)).expect("failed to push element");
+    drop(stack);
+    assert_eq!(Rc::strong_count(&rc), 1, "invalid reference count");
+}
+
+#[test]
+fn stack_swap_remove() {
+    // Remove the first, middle and last element
+    for (index, expected) in [(0, [4, 1, 2, 3]), (2, [0, 1, 4, 3]), (4, [0, 1, 2, 3])] {
+        let mut stack = Stack::<u8, 5>::new();
+        for element in 0..5 {
+            stack.push(element).expect("failed to push element");
+        }
+
+        // Validate the removed element and the remaining order
+        assert_eq!(stack.swap_remove(index), Some(index as u8), "invalid removed element");
+        assert_eq!(stack.len(), 4, "invalid stack length");
+        assert!(stack.into_iter().eq(expected), "invalid remaining elements");
+    }
+
+    // Remove a non-existing element
+    let mut stack = Stack::<u8, 5>::new();
+    stack.push(7).expect("failed to push element");
+    assert_eq!(stack.swap_remove(1), None, "unexpected removed element");
+}
+
+#[test]
+fn stack_remove() {
+    // Remove the first, middle and last element
+    for (index, expected) in [(0, [1, 2, 3, 4]), (2, [0, 1, 3, 4]), (4, [0, 1, 2, 3])] {
+        let mut stack = Stack::<u8, 5>::new();
+        for element in 0..5 {
+            stack.push(element).expect("failed to push element");
+        }
+
+        // Validate the removed element and the remaining order
+        assert_eq!(stack.remove(index), Some(index as u8), "invalid removed element");
+        assert_eq!(stack.len(), 4, "invalid stack length");
+        assert!(stack.into_iter().eq(expected), "invalid remaining elements");
+    }
+
+    // Remove a non-existing element
+    let mut stack = Stack::<u8, 5>::new();
+    stack.push(7).expect("failed to push element");
+    assert_eq!(stack.remove(1), None, "unexpected removed element");
+}
+
+#[test]
+fn stack_remove_drop() {
+    // Remove the first, middle and last element
+    for index in [0, 2, 4] {
+        let rcs: Vec<_> = (0..5).map(Rc::new).collect();
+        let mut stack = Stack::<Rc<i32>, 5>::new();
+        for rc in &rcs {
+            stack.push(Rc::clone(rc)).expect("failed to push element");
+        }
+
+        // Remove elements with both removal strategies
+        drop(stack.remove(index));
+        drop(stack.swap_remove(index.min(stack.len() - 1)));
+        let dropped = rcs.iter().filter(|rc| Rc::strong_count(rc) == 1).count();
+        assert_eq!(dropped, 2, "invalid amount of dropped elements");
+
+        // Drop the stack and ensure that each remaining element is dropped exactly once
+        drop(stack);
+        assert!(rcs.iter().all(|rc| Rc::strong_count(rc) == 1), "invalid reference count");
+    }
+}
+
+#[test]
+fn ringbuf_push_front() {
+    // Push elements to both ends
+    let mut ringbuf = RingBuf::<u8, 4>::new();
+    ringbuf.push(1).expect("failed to push element");
+    ringbuf.push_front(2).expect("failed to push element to front");
+    ringbuf.push_front(3).expect("failed to push element to front");
+    ringbuf.push(4).expect("failed to push element");
+
+    // Validate the full state and the order
+    assert!(ringbuf.is_full(), "ring buffer is not full");
+    assert!(ringbuf.push_front(5).is_err(), "unexpected success when pushing into full ring buffer");
+    for element in [3, 2, 1, 4] {
+        assert_eq!(ringbuf.pop(), Some(element), "invalid popped element");
+    }
+    assert!(ringbuf.is_empty(), "ring buffer is not empty");
+}
+
+#[test]
+fn ringbuf_deque() {
+    // Move the positions close to the wrap boundary
+    let mut ringbuf = RingBuf::<u8, 4>::new();
+    for element in 0..3 {
+        ringbuf.push(element).expect("failed to push element");
+        assert_eq!(ringbuf.pop(), Some(element), "invalid popped element");
+    }
+
+    // Interleave operations on both ends across the wrap boundary
+    ringbuf.push(2).expect("failed to push element");
+    ringbuf.push(3).expect("failed to push element");
+    ringbuf.push_front(1).expect("failed to push element to front");
+    assert_eq!(ringbuf.pop_back(), Some(3), "invalid element popped from back");
+    ringbuf.push(4).expect("failed to push element");
+    ringbuf.push_front(0).expect("failed to push element to front");
+    assert!(ringbuf.is_full(), "ring buffer is not full");
+    assert_eq!(ringbuf.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 4], "invalid element order");
+
+    // Drain the ring buffer from both ends
+    assert_eq!(ringbuf.pop_back(), Some(4), "invalid element popped from back");
+    assert_eq!(ringbuf.pop(), Some(0), "invalid popped element");
+    assert_eq!(ringbuf.pop_back(), Some(2), "invalid element popped from back");
+    assert_eq!(ringbuf.pop_back(), Some(1), "invalid element popped from back");
+    assert_eq!(ringbuf.pop_back(), None, "unexpected element in empty ring buffer");
+    assert!(ringbuf.is_empty(), "ring buffer is not empty");
+}
+
+#[test]
+fn ringbuf_iter() {
+    // Push three elements, pop one and push another one to wrap around
+    let mut ringbuf = RingBuf::<u8, 3>::new();
+    for element in 1..=3 {
+        ringbuf.push(element).expect("failed to push element");
+    }
+    assert_eq!(ringbuf.pop(), Some(1), "invalid popped element");
+    ringbuf.push(4).expect("failed to push element");
+
+    // Iterate in FIFO order without removing the elements
+    let elements: Vec<u8> = ringbuf.iter().copied().collect();
+    assert_eq!(elements, [2, 3, 4], "invalid iteration order");
+    assert_eq!(ringbuf.len(), 3, "iteration removed an element");
+}
+
+#[test]
+fn stack_iter() {
+    // Fill the stack
+    let mut stack = Stack::<u8, 4>::new();
+    for element in 1..=3 {
+        stack.push(element).expect("failed to push element");
+    }
+
+    // Modify the elements in-place and iterate without consuming the stack
+    stack.iter_mut().for_each(|element| *element *= 2);
+    let elements: Vec<u8> = stack.iter().copied().collect();
+    assert_eq!(elements, [2, 4, 6], "invalid iterated elements");
+    assert_eq!(stack.len(), 3, "iteration removed an element");
+}
+
+#[test]
+fn ringbuf_as_slices() {
+    // Slices of an empty and a contiguous ring buffer
+    let mut ringbuf = RingBuf::<u8, 4>::new();
+    assert_eq!(ringbuf.as_slices(), (&[][..], &[][..]), "invalid slices of empty ring buffer");
+    for element in 1..=3 {
+        ringbuf.push(element).expect("failed to push element");
+    }
+    assert_eq!(ringbuf.as_slices(), (&[Some(1), Some(2), Some(3)][..], &[][..]), "invalid contiguous slices");
+
+    // Wrap around and fill the buffer
+    assert_eq!(ringbuf.pop(), Some(1), "invalid popped element");
+    ringbuf.push(4).expect("failed to push element");
+    ringbuf.push(5).expect("failed to push element");
+    assert_eq!(ringbuf.as_slices(), (&[Some(2), Some(3), Some(4)][..], &[Some(5)][..]), "invalid wrapped slices");
+}
+
+#[test]
+fn ringbuf_retain() {
+    // Fill a wrapped ring buffer
+    let mut ringbuf = RingBuf::<u8, 4>::new();
+    ringbuf.push(0).expect("failed to push element");
+    ringbuf.pop().expect("failed to pop element");
+    for element in 1..=4 {
+        ringbuf.push(element).expect("failed to push element");
+    }
+
+    // Retain the odd elements and push again
+    ringbuf.retain(|element| element % 2 != 0);
+    assert_eq!(ringbuf.len(), 2, "invalid length after retain");
+    ringbuf.push(5).expect("failed to push element");
+    let elements: Vec<u8> = ringbuf.iter().copied().collect();
+    assert_eq!(elements, [1, 3, 5], "invalid retained elements");
+}
+
+#[test]
+fn capacity() {
+    assert_eq!(RingBuf::<u8, 4>::capacity(), 4, "invalid ring buffer capacity");
+    assert_eq!(Stack::<u8, 7>::capacity(), 7, "invalid stack capacity");
+}
+
+#[test]
+fn stack_extend() {
+    // Fill the stack from an array
+    let mut stack = Stack::<u8, 4>::try_from_iter([1, 2]).expect("failed to create stack from iterator");
+    assert_eq!(stack.len(), 2, "invalid stack length");
+
+    // Extend the stack beyond its capacity and validate that the pushed elements are kept
+    let mut elements = [3, 4, 5, 6].into_iter();
+    assert_eq!(stack.extend(elements.by_ref()), Err(5), "invalid first element that did not fit");
+    assert_eq!(elements.next(), Some(6), "unexpected consumption of subsequent element");
+    assert!(stack.iter().copied().eq([1, 2, 3, 4]), "invalid stack elements");
+    assert_eq!(Stack::<u8, 2>::try_from_iter([1, 2, 3]).err(), Some(3), "invalid first element that did not fit");
+}
+
+#[test]
+fn stack_extend_drop() {
+    // Partially extend the stack with reference counted values
+    let rc = Rc::new(7);
+    let mut stack = Stack::<Rc<u8>, 2>::new();
+    let overflow = stack.extend((0..3).map(|_| Rc::clone(&rc))).expect_err("unexpected success when extending stack");
+    assert_eq!(Rc::strong_count(&rc), 4, "invalid reference count");
+
+    // Drop the overflow and the stack and validate the reference count
+    drop(overflow);
+    drop(stack);
+    assert_eq!(Rc::strong_count(&rc), 1, "invalid reference count");
+}
+
+#[test]
+fn ringbuf_len_mixed() {
+    // Mix pushes to the back and the front, and validate the length in each state
+    let mut ringbuf = RingBuf::<u8, 3>::new();
+    ringbuf.push(2).expect("failed to push element");
+    ringbuf.push_front(1).expect("failed to push element");
+    ringbuf.push(3).expect("failed to push element");
+    assert!(ringbuf.is_full(), "ring buffer is not full");
+    assert_eq!(ringbuf.push_front(0), Err(0), "unexpected success when pushing into full ring buffer");
+
+    // Drain the ring buffer
+    for (len, element) in (0..3).rev().zip(1..=3) {
+        assert_eq!(ringbuf.pop(), Some(element), "invalid element");
+        assert_eq!(ringbuf.len(), len, "invalid ring buffer length");
+    }
+    assert!(ringbuf.is_empty(), "ring buffer is not empty");
+    assert_eq!(ringbuf.pop(), None, "unexpected element in empty ring buffer");
+
+    // A ring buffer without capacity is always both empty and full
+    let mut ringbuf = RingBuf::<u8, 0>::new();
+    assert!(ringbuf.is_empty() && ringbuf.is_full(), "invalid state of ring buffer without capacity");
+    assert_eq!(ringbuf.push(7), Err(7), "unexpected success when pushing into ring buffer without capacity");
+}
+
+#[test]
+fn ringbuf_pop_if() {
+    // Pop the next element conditionally
+    let mut ringbuf: RingBuf<u8, 2> = RingBuf::new();
+    ringbuf.push(7).expect("failed to push element");
+    ringbuf.push(4).expect("failed to push element");
+    assert_eq!(ringbuf.pop_if(|element| *element == 4), None, "unexpected pop");
+    assert_eq!(ringbuf.len(), 2, "invalid length after rejected pop");
+    assert_eq!(ringbuf.pop_if(|element| *element == 7), Some(7), "invalid popped element");
+
+    // Push and pop again to ensure the buffer is still consistent
+    ringbuf.push(1).expect("failed to push element");
+    assert_eq!(ringbuf.pop(), Some(4), "invalid popped element");
+    assert_eq!(ringbuf.pop(), Some(1), "invalid popped element");
+    assert_eq!(ringbuf.pop_if(|_| true), None, "unexpected pop from empty buffer");
+}
+
+#[test]
+fn const_collections() {
+    /// A stack that is prepopulated at compile time
+    const STACK: Stack<u8, 4> = {
+        let mut stack = Stack::new();
+        assert!(stack.push(7).is_ok() && stack.push(4).is_ok());
+        stack
+    };
+    /// A ring buffer that is prepopulated at compile time
+    const RINGBUF: RingBuf<u8, 4> = {
+        let mut ringbuf = RingBuf::new();
+        assert!(ringbuf.push(7).is_ok() && ringbuf.push(4).is_ok());
+        assert!(matches!(ringbuf.pop(), Some(7)));
+        ringbuf
+    };
+
+    // Validate the prepopulated collections
+    assert_eq!(STACK.iter().copied().collect::<Vec<_>>(), [7, 4], "invalid stack elements");
+    assert_eq!(RINGBUF.len(), 1, "invalid ring buffer length");
+    assert_eq!(RINGBUF.peek(), Some(&4), "invalid ring buffer element");
+}
+
+#[test]
+fn ringbuf_drain() {
+    // Drain a wrapped ring buffer
+    let mut ringbuf: RingBuf<u8, 3> = RingBuf::new();
+    for element in [1, 2, 3] {
+        ringbuf.push(element).expect("failed to push element");
+    }
+    assert_eq!(ringbuf.pop(), Some(1), "invalid popped element");
+    ringbuf.push(4).expect("failed to push element");
+    assert_eq!(ringbuf.drain().collect::<Vec<_>>(), [2, 3, 4], "invalid drained elements");
+    assert!(ringbuf.is_empty(), "ring buffer is not empty after draining");
+
+    // Drop a partially consumed drain
+    let value = Rc::new(7u8);
+    let mut values: RingBuf<Rc<u8>, 3> = RingBuf::new();
+    values.push(value.clone()).expect("failed to push element");
+    values.push(value.clone()).expect("failed to push element");
+    assert_eq!(values.drain().next().as_deref(), Some(&7), "invalid drained element");
+    assert!(values.is_empty(), "ring buffer is not empty after draining");
+    assert_eq!(Rc::strong_count(&value), 1, "remaining elements were not dropped");
+}
+
+#[test]
+fn stack_get() {
+    // Access the elements without consuming the stack
+    let mut stack: Stack<u8, 4> = Stack::new();
+    assert_eq!(stack.last(), None, "unexpected element in empty stack");
+    stack.extend([7, 4]).expect("failed to extend stack");
+    assert_eq!(stack.get(0), Some(&7), "invalid element");
+    assert_eq!(stack.get(1), Some(&4), "invalid element");
+    assert_eq!(stack.get(2), None, "unexpected element beyond length");
+    assert_eq!(stack.last(), Some(&4), "invalid topmost element");
+}
+
+#[test]
+fn ringbuf_zero_capacity() {
+    // Access an empty zero-capacity ring buffer
+    let mut ringbuf = RingBuf::<u8, 0>::new();
+    assert!(ringbuf.is_full(), "zero-capacity ring buffer is not full");
+    assert_eq!(ringbuf.peek(), None, "unexpected element in zero-capacity ring buffer");
+    assert_eq!(ringbuf.pop_if(|_| true), None, "unexpected element in zero-capacity ring buffer");
+
+    // Ensure that no element can be inserted
+    assert_eq!(ringbuf.push(7), Err(7), "unexpected success when pushing into zero-capacity ring buffer");
+    assert_eq!(ringbuf.push_front(7), Err(7), "unexpected success when pushing into zero-capacity ring buffer");
+    assert_eq!(ringbuf.push_overwrite(7), Some(7), "element was not rejected by zero-capacity ring buffer");
+    assert!(ringbuf.is_empty(), "zero-capacity ring buffer is not empty");
+    assert_eq!(ringbuf.pop(), None, "unexpected element in zero-capacity ring buffer");
+}
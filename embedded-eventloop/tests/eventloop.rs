@@ -0,0 +1,1662 @@
+//! The event loop
+
+use embedded_eventloop::{
+    boxes::Box,
+    runtime::{LinkedRuntime, Runtime},
+    BootstrapError, EventLoop, ListenError, ListenerId, LocalEventLoop, OverflowPolicy, SendError, Sender,
+};
+use embedded_eventloop_std::testing::{RuntimeCall, TestRuntime};
+use std::{
+    any::TypeId,
+    future::Future,
+    panic,
+    pin::pin,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn eventloop_run_until_idle() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The amount of processed events
+    static PROCESSED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the event down and re-sends it until it reaches zero
+    fn countdown(count: u32) -> Option<u32> {
+        PROCESSED.fetch_add(1, SeqCst);
+        if let Some(count) = count.checked_sub(1) {
+            EVENTLOOP.send(count).expect("failed to re-send event");
+        }
+        None
+    }
+
+    // Seed the countdown and run until all re-sent events are processed
+    EVENTLOOP.bootstrap(9u32, countdown).expect("failed to bootstrap listener");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(PROCESSED.load(SeqCst), 10, "invalid amount of processed events");
+    assert!(!EVENTLOOP.poll(), "unexpected pending event");
+}
+
+#[test]
+fn eventloop_run_max() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+
+    /// Re-sends the event forever
+    fn resend(event: u32) -> Option<u32> {
+        EVENTLOOP.send(event).expect("failed to re-send event");
+        None
+    }
+
+    // Ensure the endless re-send loop is capped
+    EVENTLOOP.bootstrap(7u32, resend).expect("failed to bootstrap listener");
+    assert_eq!(EVENTLOOP.run_max(16), 16, "invalid amount of processed events");
+    assert!(EVENTLOOP.poll(), "missing pending event");
+}
+
+#[test]
+fn eventloop_poll() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 32, 32, 1, TestRuntime> = EventLoop::new();
+    /// The amount of processed events
+    static PROCESSED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the event
+    fn count(_: u8) -> Option<u8> {
+        PROCESSED.fetch_add(1, SeqCst);
+        None
+    }
+
+    // Poll the empty backlog and ensure that the event loop does not wait
+    EVENTLOOP.listen(count).expect("failed to register listener");
+    TestRuntime::take_calls();
+    assert!(!EVENTLOOP.poll(), "unexpected event in empty backlog");
+    assert!(!TestRuntime::take_calls().contains(&RuntimeCall::WaitForEvent), "poll waited for an event");
+
+    // Poll a pending event
+    EVENTLOOP.send(7u8).expect("failed to send event");
+    assert!(EVENTLOOP.poll(), "pending event was not processed");
+    assert_eq!(PROCESSED.load(SeqCst), 1, "invalid amount of processed events");
+    assert!(!EVENTLOOP.poll(), "unexpected event after the backlog has been drained");
+    assert!(!TestRuntime::take_calls().contains(&RuntimeCall::WaitForEvent), "poll waited for an event");
+}
+
+#[test]
+fn eventloop_introspection() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 4, 4> = EventLoop::new();
+
+    /// Consumes the event
+    fn consume(_: u8) -> Option<u8> {
+        None
+    }
+
+    // Register a listener and fill the backlog
+    let id = EVENTLOOP.listen(consume).expect("failed to register listener");
+    assert_eq!(EVENTLOOP.listeners_len(), 1, "invalid amount of listeners");
+    for event in 0..4u8 {
+        assert_eq!(EVENTLOOP.backlog_len(), event as usize, "invalid backlog length");
+        EVENTLOOP.send(event).expect("failed to send event");
+    }
+    assert!(EVENTLOOP.is_backlog_full(), "backlog is not full");
+    assert_eq!(EVENTLOOP.backlog_len(), 4, "invalid backlog length");
+
+    // Drain the backlog and remove the listener
+    EVENTLOOP.run_until_idle();
+    assert_eq!(EVENTLOOP.backlog_len(), 0, "invalid backlog length");
+    assert!(EVENTLOOP.unlisten(id), "failed to remove listener");
+    assert!(!EVENTLOOP.unlisten(id), "unexpected success when removing a removed listener");
+    assert_eq!(EVENTLOOP.listeners_len(), 0, "invalid amount of listeners");
+}
+
+#[test]
+fn eventloop_unlisten() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The amount of processed events
+    static PROCESSED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the event
+    fn count(_: u8) -> Option<u8> {
+        PROCESSED.fetch_add(1, SeqCst);
+        None
+    }
+
+    // Process an event, then remove the listener
+    let id = EVENTLOOP.listen(count).expect("failed to register listener");
+    EVENTLOOP.send(7u8).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert!(EVENTLOOP.unlisten(id), "failed to remove listener");
+
+    // Ensure that the removed listener does not fire anymore
+    EVENTLOOP.send(4u8).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(PROCESSED.load(SeqCst), 1, "removed listener was invoked");
+}
+
+#[test]
+fn eventloop_unlisten_stale() {
+    /// The event loop with a single listener slot
+    static EVENTLOOP: EventLoop<64, 32, 1> = EventLoop::new();
+    /// The amount of processed events
+    static PROCESSED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Ignores the event
+    fn ignore(_: u8) -> Option<u8> {
+        None
+    }
+    /// Counts the event
+    fn count(_: u8) -> Option<u8> {
+        PROCESSED.fetch_add(1, SeqCst);
+        None
+    }
+
+    // Remove a listener and reuse its slot
+    let stale_id = EVENTLOOP.listen(ignore).expect("failed to register listener");
+    assert!(EVENTLOOP.unlisten(stale_id), "failed to remove listener");
+    let id = EVENTLOOP.listen(count).expect("failed to register listener");
+
+    // Ensure that the stale handle does not remove the new listener from the reused slot
+    assert!(!EVENTLOOP.unlisten(stale_id), "stale handle removed the listener in the reused slot");
+    EVENTLOOP.send(7u8).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(PROCESSED.load(SeqCst), 1, "listener in the reused slot was not invoked");
+    assert!(EVENTLOOP.unlisten(id), "failed to remove listener");
+}
+
+#[test]
+fn eventloop_unlisten_during_dispatch() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The handle of the listener that removes itself
+    static ID: Mutex<Option<ListenerId>> = Mutex::new(None);
+    /// The amount of processed events
+    static PROCESSED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the event and removes itself
+    fn once(event: u8) -> Option<u8> {
+        PROCESSED.fetch_add(1, SeqCst);
+        let id = ID.lock().expect("failed to lock handle").take().expect("missing handle");
+        assert!(EVENTLOOP.unlisten(id), "failed to remove running listener");
+        Some(event)
+    }
+    /// Consumes the event; the chain must continue after the running listener has removed itself
+    fn next(_: u8) -> Option<u8> {
+        PROCESSED.fetch_add(10, SeqCst);
+        None
+    }
+
+    // Register the listeners and process two events
+    let id = EVENTLOOP.listen(once).expect("failed to register listener");
+    *ID.lock().expect("failed to lock handle") = Some(id);
+    EVENTLOOP.listen(next).expect("failed to register listener");
+    EVENTLOOP.send(7u8).expect("failed to send event");
+    EVENTLOOP.send(4u8).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+
+    // Validate that the removed listener completed its invocation and has not been invoked again
+    assert_eq!(PROCESSED.load(SeqCst), 21, "invalid invocations");
+    assert_eq!(EVENTLOOP.listeners_len(), 1, "invalid amount of listeners");
+}
+
+#[test]
+fn eventloop_listen_fn() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The running sum as seen by the listener
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    // Register a listener that keeps a running sum as state
+    let mut sum = 0;
+    let listener = move |value: usize| {
+        sum += value;
+        SUM.store(sum, SeqCst);
+        None
+    };
+    assert!(EVENTLOOP.listen_fn(listener).is_ok(), "failed to register stateful listener");
+
+    // Send some values
+    for value in 1..=4usize {
+        EVENTLOOP.send(value).expect("failed to send event");
+    }
+    EVENTLOOP.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 10, "invalid listener state");
+}
+
+#[test]
+fn eventloop_listen_fn_constraints_size() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<16> = EventLoop::new();
+
+    // Create a listener that is too large
+    let state = [0u64; 4];
+    let listener = move |value: u64| Some(value + state[0]);
+    let result = EVENTLOOP.listen_fn(listener);
+    assert!(matches!(result, Err(ListenError::TooLarge { available: 16, .. })), "invalid error for too large listener");
+}
+
+#[test]
+fn eventloop_listen_full() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 32, 1> = EventLoop::new();
+
+    /// Consumes the event
+    fn consume(_event: u8) -> Option<u8> {
+        None
+    }
+
+    // Exceed the listener capacity and recover the callback
+    EVENTLOOP.listen(consume).expect("failed to register listener");
+    let error = EVENTLOOP.listen(consume).expect_err("unexpected success when exceeding the listener capacity");
+    assert!(matches!(error, ListenError::Full { capacity: 1, .. }), "invalid error for exceeded capacity");
+    assert_eq!(error.into_inner()(7), None, "failed to recover callback");
+}
+
+#[test]
+fn eventloop_listen_once() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The amount of invocations
+    static INVOKED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the invocation and passes the event on
+    fn count(event: u16) -> Option<u16> {
+        INVOKED.fetch_add(1, SeqCst);
+        Some(event)
+    }
+
+    // Register a one-shot listener followed by a normal listener for the same type
+    EVENTLOOP.listen_once(count).expect("failed to register one-shot listener");
+    EVENTLOOP.listen(count).expect("failed to register listener");
+
+    // Send some events
+    for event in 0..3u16 {
+        EVENTLOOP.send(event).expect("failed to send event");
+    }
+    EVENTLOOP.run_until_idle();
+    assert_eq!(INVOKED.load(SeqCst), 4, "invalid amount of invocations");
+    assert_eq!(EVENTLOOP.listeners_len(), 1, "one-shot listener has not been removed");
+}
+
+#[test]
+fn eventloop_has_listener() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+
+    /// Consumes the event
+    fn consume(_: i8) -> Option<i8> {
+        None
+    }
+
+    // Register and remove a listener
+    assert!(!EVENTLOOP.has_listener::<i8>(), "unexpected listener");
+    let id = EVENTLOOP.listen(consume).expect("failed to register listener");
+    assert!(EVENTLOOP.has_listener::<i8>(), "missing listener");
+    assert!(!EVENTLOOP.has_listener::<u8>(), "unexpected listener for different type");
+    EVENTLOOP.unlisten(id);
+    assert!(!EVENTLOOP.has_listener::<i8>(), "unexpected listener after removal");
+}
+
+#[test]
+fn eventloop_enter_concurrent_send() {
+    /// The amount of producers
+    const PRODUCERS: usize = 4;
+    /// The amount of events per producer
+    const EVENTS: usize = 2500;
+
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 8> = EventLoop::new();
+    /// The amount of processed events
+    static PROCESSED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the event
+    fn count(_: u64) -> Option<u64> {
+        PROCESSED.fetch_add(1, SeqCst);
+        None
+    }
+
+    // Start the event loop
+    EVENTLOOP.listen(count).expect("failed to register listener");
+    thread::spawn(|| EVENTLOOP.enter());
+
+    // Send events concurrently, retrying if the backlog is full
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|_| {
+            thread::spawn(|| {
+                for event in 0..EVENTS as u64 {
+                    while EVENTLOOP.send(event).is_err() {
+                        thread::yield_now();
+                    }
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().expect("producer panicked");
+    }
+
+    // Wait until all events have been processed; a lost wakeup would stall the loop with events pending
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while PROCESSED.load(SeqCst) < PRODUCERS * EVENTS {
+        assert!(Instant::now() < deadline, "event loop stalled with pending events");
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn eventloop_send_overwrite() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 2> = EventLoop::new();
+    /// The sum of the processed events
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    /// Sums the event up
+    fn sum(event: usize) -> Option<usize> {
+        SUM.fetch_add(event, SeqCst);
+        None
+    }
+
+    // Overflow the backlog so that only the latest events remain
+    EVENTLOOP.listen(sum).expect("failed to register listener");
+    for event in [1, 10, 100, 1000usize] {
+        EVENTLOOP.send_overwrite(event).expect("failed to send event");
+    }
+    EVENTLOOP.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 1100, "invalid set of processed events");
+}
+
+#[test]
+fn eventloop_enter_with_idle() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The amount of idle transitions
+    static IDLE: AtomicUsize = AtomicUsize::new(0);
+    /// The amount of processed events
+    static PROCESSED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the idle transition
+    fn idle() {
+        IDLE.fetch_add(1, SeqCst);
+    }
+    /// Counts the event
+    fn count(_: i64) -> Option<i64> {
+        PROCESSED.fetch_add(1, SeqCst);
+        None
+    }
+
+    /// Waits until `counter` reaches `value`
+    fn wait_for(counter: &AtomicUsize, value: usize) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while counter.load(SeqCst) < value {
+            assert!(Instant::now() < deadline, "timeout while waiting for counter");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    // Start the event loop and wait for the first idle transition
+    EVENTLOOP.listen(count).expect("failed to register listener");
+    thread::spawn(|| EVENTLOOP.enter_with_idle(idle));
+    wait_for(&IDLE, 1);
+
+    // Wake the loop without an event and ensure the hook is not called again
+    for _ in 0..16 {
+        embedded_eventloop_std::runtime::_runtime_sendevent_ZMWrWpGO();
+    }
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(IDLE.load(SeqCst), 1, "idle hook called without transition to idle");
+
+    // Send an event and wait for the next idle transition
+    EVENTLOOP.send(7i64).expect("failed to send event");
+    wait_for(&PROCESSED, 1);
+    wait_for(&IDLE, 2);
+}
+
+#[test]
+fn eventloop_send_front() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The order of the processed events as decimal digits
+    static ORDER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Appends the event to the order
+    fn record(event: u128) -> Option<u128> {
+        ORDER.fetch_update(SeqCst, SeqCst, |order| Some(order * 10 + event as usize)).expect("failed to record event");
+        None
+    }
+
+    // Send normal events, then urgent events
+    EVENTLOOP.listen(record).expect("failed to register listener");
+    EVENTLOOP.send(1u128).expect("failed to send event");
+    EVENTLOOP.send(2u128).expect("failed to send event");
+    EVENTLOOP.send_front(3u128).expect("failed to send urgent event");
+    EVENTLOOP.send_front(4u128).expect("failed to send urgent event");
+
+    // Urgent events are processed first in LIFO order
+    EVENTLOOP.run_until_idle();
+    assert_eq!(ORDER.load(SeqCst), 4312, "invalid event order");
+}
+
+#[test]
+fn eventloop_send_prio() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 32, 32, 2> = EventLoop::new();
+    /// The last processed event
+    static LAST: AtomicUsize = AtomicUsize::new(0);
+
+    /// Records the event
+    fn record(event: usize) -> Option<usize> {
+        LAST.store(event, SeqCst);
+        None
+    }
+
+    // Flood the low-priority backlog, then send a high-priority event
+    EVENTLOOP.listen(record).expect("failed to register listener");
+    for event in 1..=32usize {
+        EVENTLOOP.send(event).expect("failed to send event");
+    }
+    assert!(EVENTLOOP.send(33usize).is_err(), "low-priority backlog is not full");
+    EVENTLOOP.send_prio(1, 1000usize).expect("failed to send high-priority event");
+    assert_eq!(EVENTLOOP.backlog_len(), 33, "invalid backlog length");
+
+    // The high-priority event is processed next, followed by the low-priority events in order
+    assert!(EVENTLOOP.poll(), "no pending event");
+    assert_eq!(LAST.load(SeqCst), 1000, "high-priority event was not processed first");
+    assert!(EVENTLOOP.poll(), "no pending event");
+    assert_eq!(LAST.load(SeqCst), 1, "invalid low-priority event order");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(LAST.load(SeqCst), 32, "invalid low-priority event order");
+}
+
+#[test]
+fn eventloop_send_checked() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 2, 32> = EventLoop::new();
+
+    /// Consumes the event
+    fn consume(_event: u16) -> Option<u16> {
+        None
+    }
+    /// Inspects the event
+    fn inspect(_event: &u16) {}
+
+    // Events without a listener are rejected and do not occupy the backlog
+    assert_eq!(EVENTLOOP.send_checked(7u16), Err(SendError::NoListener(7)), "event without listener was accepted");
+    assert_eq!(EVENTLOOP.backlog_len(), 0, "rejected event was enqueued");
+
+    // Events that would only be seen by an observer are rejected as well
+    EVENTLOOP.observe(inspect).expect("failed to register observer");
+    assert!(!EVENTLOOP.has_listener::<u16>(), "observer was counted as listener");
+    assert_eq!(EVENTLOOP.send_checked(8u16), Err(SendError::NoListener(8)), "event with only an observer was accepted");
+    assert_eq!(EVENTLOOP.backlog_len(), 0, "rejected event was enqueued");
+
+    // Events with a listener are accepted until the backlog is reached
+    EVENTLOOP.listen(consume).expect("failed to register listener");
+    EVENTLOOP.send_checked(1u16).expect("failed to send event");
+    EVENTLOOP.send_checked(2u16).expect("failed to send event");
+    assert_eq!(EVENTLOOP.send_checked(3u16), Err(SendError::BacklogFull(3)), "event was accepted by full backlog");
+    assert_eq!(EVENTLOOP.run_max(usize::MAX), 2, "invalid amount of processed events");
+}
+
+#[test]
+fn eventloop_observe() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The order of the invoked callbacks as decimal digits
+    static ORDER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Appends the given digit to the order
+    fn record(digit: usize) {
+        ORDER.fetch_update(SeqCst, SeqCst, |order| Some(order * 10 + digit)).expect("failed to record callback");
+    }
+    /// Consumes the event
+    fn consume(_event: i16) -> Option<i16> {
+        record(1);
+        None
+    }
+    /// Observes the event
+    fn observe_a(event: &i16) {
+        assert_eq!(*event, 7, "invalid event");
+        record(2);
+    }
+    /// Observes the event
+    fn observe_b(event: &i16) {
+        assert_eq!(*event, 7, "invalid event");
+        record(3);
+    }
+
+    // Register the consumer before the observers
+    EVENTLOOP.listen(consume).expect("failed to register listener");
+    EVENTLOOP.observe(observe_a).expect("failed to register observer");
+    EVENTLOOP.observe(observe_b).expect("failed to register observer");
+
+    // Observers are invoked first, and the event is consumed afterwards
+    EVENTLOOP.send(7i16).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(ORDER.load(SeqCst), 231, "invalid callback order");
+}
+
+#[test]
+fn eventloop_on_unhandled() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The amount of unhandled events
+    static UNHANDLED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Consumes even events and passes odd events on
+    fn consume_even(event: u8) -> Option<u8> {
+        (!event.is_multiple_of(2)).then_some(event)
+    }
+    /// Counts the unhandled event
+    fn unhandled(event: &Box<64>) {
+        assert_eq!(event.inner_type_id(), TypeId::of::<u8>(), "invalid unhandled event type");
+        UNHANDLED.fetch_add(1, SeqCst);
+    }
+
+    // Send an event without listener
+    EVENTLOOP.on_unhandled(unhandled);
+    EVENTLOOP.send(1u8).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(UNHANDLED.load(SeqCst), 1, "event without listener was not reported");
+
+    // Send a consumed and a passed-on event
+    EVENTLOOP.listen(consume_even).expect("failed to register listener");
+    EVENTLOOP.send(2u8).expect("failed to send event");
+    EVENTLOOP.send(3u8).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(UNHANDLED.load(SeqCst), 2, "invalid amount of unhandled events");
+}
+
+#[test]
+#[cfg(feature = "metrics")]
+fn eventloop_metrics() {
+    use embedded_eventloop::EventLoopMetrics;
+
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 2, 32> = EventLoop::new();
+
+    /// Consumes even events and passes odd events on
+    fn consume_even(event: u32) -> Option<u32> {
+        (!event.is_multiple_of(2)).then_some(event)
+    }
+
+    // Fill the backlog and drop an event
+    EVENTLOOP.listen(consume_even).expect("failed to register listener");
+    EVENTLOOP.send(1u32).expect("failed to send event");
+    EVENTLOOP.send(2u32).expect("failed to send event");
+    EVENTLOOP.send(3u32).expect_err("full backlog accepted event");
+    EVENTLOOP.send_overwrite(4u32).expect("failed to send event");
+
+    // Process the events and validate the metrics
+    EVENTLOOP.run_until_idle();
+    let metrics = EventLoopMetrics { enqueued: 3, dispatched: 2, dropped: 2, unhandled: 0 };
+    assert_eq!(EVENTLOOP.metrics(), metrics, "invalid metrics");
+
+    // Send an unhandled event
+    EVENTLOOP.send(5u32).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    let metrics = EventLoopMetrics { enqueued: 4, dispatched: 3, dropped: 2, unhandled: 1 };
+    assert_eq!(EVENTLOOP.metrics(), metrics, "invalid metrics");
+}
+
+#[test]
+fn eventloop_enter_until() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// Whether the event loop should stop
+    static STOP: AtomicBool = AtomicBool::new(false);
+
+    /// Requests the event loop to stop on a zero event
+    fn stop_on_zero(event: i8) -> Option<i8> {
+        STOP.store(event == 0, SeqCst);
+        None
+    }
+
+    // Send some events and stop in between
+    EVENTLOOP.listen(stop_on_zero).expect("failed to register listener");
+    for event in [1i8, 0, 2] {
+        EVENTLOOP.send(event).expect("failed to send event");
+    }
+    EVENTLOOP.enter_until(|| STOP.load(SeqCst));
+    assert_eq!(EVENTLOOP.backlog_len(), 1, "pending event was not kept in the backlog");
+}
+
+#[test]
+fn eventloop_send_coalesced() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The amount of processed events
+    static PROCESSED: AtomicUsize = AtomicUsize::new(0);
+
+    /// A redraw event
+    #[derive(Debug, PartialEq)]
+    struct Redraw(u8);
+
+    /// Counts the event
+    fn redraw(_event: Redraw) -> Option<Redraw> {
+        PROCESSED.fetch_add(1, SeqCst);
+        None
+    }
+
+    // Send duplicate events
+    EVENTLOOP.listen(redraw).expect("failed to register listener");
+    for _ in 0..8 {
+        EVENTLOOP.send_coalesced(Redraw(0)).expect("failed to send event");
+    }
+    EVENTLOOP.send_coalesced(Redraw(1)).expect("failed to send event");
+    assert_eq!(EVENTLOOP.backlog_len(), 2, "duplicate events were not coalesced");
+
+    // Process the events and send the event again
+    EVENTLOOP.run_until_idle();
+    EVENTLOOP.send_coalesced(Redraw(0)).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(PROCESSED.load(SeqCst), 3, "invalid amount of processed events");
+}
+
+#[test]
+fn eventloop_send_broadcast() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The invoked listeners as bit flags
+    static INVOKED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Records the listener and ends the chain
+    fn listener_a(event: usize) -> Option<usize> {
+        INVOKED.fetch_or(event & 0b001, SeqCst);
+        None
+    }
+    /// Records the listener and ends the chain
+    fn listener_b(event: usize) -> Option<usize> {
+        INVOKED.fetch_or(event & 0b010, SeqCst);
+        None
+    }
+    /// Records the listener and ends the chain
+    fn listener_c(event: usize) -> Option<usize> {
+        INVOKED.fetch_or(event & 0b100, SeqCst);
+        None
+    }
+
+    // Broadcast an event to all listeners
+    EVENTLOOP.listen(listener_a).expect("failed to register listener");
+    EVENTLOOP.listen(listener_b).expect("failed to register listener");
+    EVENTLOOP.listen(listener_c).expect("failed to register listener");
+    EVENTLOOP.send_broadcast(0b111usize).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(INVOKED.load(SeqCst), 0b111, "not all listeners have been invoked");
+}
+
+#[test]
+fn eventloop_send_all() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 4, 32> = EventLoop::new();
+    /// The sum of the processed events
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    /// Sums the event
+    fn sum(event: u64) -> Option<u64> {
+        SUM.fetch_add(event as usize, SeqCst);
+        None
+    }
+
+    // Send more events than fit into the backlog
+    EVENTLOOP.listen(sum).expect("failed to register listener");
+    assert_eq!(EVENTLOOP.send_all(1..=6u64), Err((4, 5)), "invalid first failing event");
+    assert_eq!(EVENTLOOP.backlog_len(), 4, "invalid backlog length");
+
+    // Process the events and send the remaining events
+    EVENTLOOP.run_until_idle();
+    EVENTLOOP.send_all([5u64, 6]).expect("failed to send events");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 21, "invalid sum of processed events");
+}
+
+#[test]
+fn eventloop_sender() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 2, 32> = EventLoop::new();
+    /// The sum of the processed events
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    /// A driver that is not generic over the event loop capacities
+    struct Driver {
+        /// The event sender
+        sender: Sender<'static, u16>,
+    }
+
+    /// Sums the event
+    fn sum(event: u16) -> Option<u16> {
+        SUM.fetch_add(usize::from(event), SeqCst);
+        None
+    }
+
+    // Send events via the driver until the backlog is reached
+    EVENTLOOP.listen(sum).expect("failed to register listener");
+    let driver = Driver { sender: EVENTLOOP.sender() };
+    driver.sender.send(3).expect("failed to send event");
+    driver.sender.send(4).expect("failed to send event");
+    assert_eq!(driver.sender.send(5), Err(5), "full backlog accepted event");
+
+    // Process the events
+    EVENTLOOP.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 7, "invalid sum of processed events");
+}
+
+#[test]
+fn eventloop_drain_into() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The order of the processed events as decimal digits
+    static ORDER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Appends the event to the order
+    fn record(event: u16) -> Option<u16> {
+        ORDER
+            .fetch_update(SeqCst, SeqCst, |order| Some(order * 10 + usize::from(event)))
+            .expect("failed to record event");
+        None
+    }
+
+    // Send interleaved events of different types
+    EVENTLOOP.listen(record).expect("failed to register listener");
+    for event in 1..=3u8 {
+        EVENTLOOP.send(event).expect("failed to send event");
+        EVENTLOOP.send(u16::from(event)).expect("failed to send event");
+    }
+
+    // Drain the `u8` events into a stack that is too small
+    let (drained, dropped) = EVENTLOOP.drain_into::<u8, 2>();
+    assert_eq!(drained.iter().copied().collect::<Vec<_>>(), [1, 2], "invalid drained events");
+    assert_eq!(dropped, 1, "invalid amount of dropped events");
+
+    // The other events are preserved in order
+    assert_eq!(EVENTLOOP.backlog_len(), 3, "invalid backlog length");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(ORDER.load(SeqCst), 123, "invalid event order");
+}
+
+#[test]
+fn eventloop_new_unique() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new_unique();
+
+    /// Consumes the event
+    fn consume(_event: i32) -> Option<i32> {
+        None
+    }
+
+    // Register a listener and reject a duplicate registration
+    assert_eq!(EVENTLOOP.listener_count::<i32>(), 0, "invalid listener count");
+    let id = EVENTLOOP.listen(consume).expect("failed to register listener");
+    let result = EVENTLOOP.listen(consume);
+    assert!(matches!(result, Err(ListenError::DuplicateType { .. })), "duplicate listener was accepted");
+    assert_eq!(EVENTLOOP.listener_count::<i32>(), 1, "invalid listener count");
+
+    // Register the listener again after it has been removed
+    EVENTLOOP.unlisten(id);
+    EVENTLOOP.listen(consume).expect("failed to register listener");
+    assert_eq!(EVENTLOOP.listener_count::<i32>(), 1, "invalid listener count");
+}
+
+#[test]
+#[cfg(all(feature = "breadcrumb", feature = "type-name"))]
+fn eventloop_breadcrumb() {
+    use embedded_eventloop::Breadcrumb;
+    use std::sync::Mutex;
+
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The breadcrumb during dispatch
+    static BREADCRUMB: Mutex<Option<Breadcrumb>> = Mutex::new(None);
+
+    /// Records the current breadcrumb
+    fn record(_event: u64) -> Option<u64> {
+        *BREADCRUMB.lock().expect("failed to lock breadcrumb") = embedded_eventloop::current_breadcrumb();
+        None
+    }
+
+    // Dispatch an event and validate the breadcrumb
+    EVENTLOOP.listen(record).expect("failed to register listener");
+    EVENTLOOP.send(7u64).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    let breadcrumb = BREADCRUMB.lock().expect("failed to lock breadcrumb").expect("missing breadcrumb");
+    assert_eq!(breadcrumb.event, "u64", "invalid event type name");
+    assert_eq!(breadcrumb.listener, "fn(u64) -> core::option::Option<u64>", "invalid listener type name");
+}
+
+#[test]
+fn eventloop_retain_events() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The order of the processed events as decimal digits
+    static ORDER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Appends the event to the order
+    fn record_u8(event: u8) -> Option<u8> {
+        ORDER
+            .fetch_update(SeqCst, SeqCst, |order| Some(order * 10 + usize::from(event)))
+            .expect("failed to record event");
+        None
+    }
+    /// Appends the event to the order
+    fn record_u16(event: u16) -> Option<u16> {
+        ORDER
+            .fetch_update(SeqCst, SeqCst, |order| Some(order * 10 + usize::from(event)))
+            .expect("failed to record event");
+        None
+    }
+
+    // Send interleaved events of different types
+    EVENTLOOP.listen(record_u8).expect("failed to register listener");
+    EVENTLOOP.listen(record_u16).expect("failed to register listener");
+    for event in 1..=3u8 {
+        EVENTLOOP.send(event).expect("failed to send event");
+        EVENTLOOP.send(u16::from(event) + 3).expect("failed to send event");
+    }
+
+    // Remove the even `u8` events and keep all other events in order
+    assert_eq!(EVENTLOOP.retain_events::<u8>(|event| event % 2 != 0), 1, "invalid amount of removed events");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(ORDER.load(SeqCst), 14536, "invalid event order");
+}
+
+#[test]
+fn eventloop_runtime() {
+    /// A mock runtime for a single thread that counts the raised events
+    #[derive(Debug)]
+    struct MockRuntime;
+    impl Runtime for MockRuntime {
+        fn wait_for_event() {
+            unreachable!("unexpected wait for event");
+        }
+        fn send_event() {
+            SENT.fetch_add(1, SeqCst);
+        }
+        fn with_critical_section(code: &mut dyn FnMut()) {
+            code();
+        }
+    }
+
+    static EVENTLOOP: EventLoop<64, 32, 32, 1, MockRuntime> = EventLoop::new();
+    static SENT: AtomicUsize = AtomicUsize::new(0);
+    static PROCESSED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the processed events
+    fn count(_: u8) -> Option<u8> {
+        PROCESSED.fetch_add(1, SeqCst);
+        None
+    }
+
+    // Send some events and process them via the mock runtime
+    EVENTLOOP.listen(count).expect("failed to register listener");
+    for event in 0..3u8 {
+        EVENTLOOP.send(event).expect("failed to send event");
+    }
+    EVENTLOOP.run_until_idle();
+    assert_eq!(SENT.load(SeqCst), 3, "invalid amount of raised events");
+    assert_eq!(PROCESSED.load(SeqCst), 3, "invalid amount of processed events");
+}
+
+#[test]
+fn eventloop_bootstrap_many() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 2, 32> = EventLoop::new();
+    /// The amount of processed events
+    static PROCESSED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts down until zero
+    fn countdown(count: u32) -> Option<u32> {
+        PROCESSED.fetch_add(1, SeqCst);
+        count.checked_sub(1)
+    }
+
+    // Seed more events than fit into the backlog
+    let (id, overflow) = EVENTLOOP.bootstrap_many([1u32, 2, 3], countdown).expect("failed to register listener");
+    assert_eq!(overflow, Some(3), "invalid first overflowing event");
+    assert_eq!(EVENTLOOP.listener_count::<u32>(), 1, "invalid amount of listeners");
+
+    // Process the seeds and their follow-up events
+    EVENTLOOP.run_until_idle();
+    assert_eq!(PROCESSED.load(SeqCst), 2, "invalid amount of processed events");
+    assert!(EVENTLOOP.unlisten(id), "failed to remove listener");
+}
+
+#[test]
+fn eventloop_bootstrap_error() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 1, 32> = EventLoop::new_unique();
+
+    /// Ignores the event
+    fn ignore_u8(_: u8) -> Option<u8> {
+        None
+    }
+    /// Ignores the event
+    fn ignore_u16(_: u16) -> Option<u16> {
+        None
+    }
+
+    // Fill the backlog, so that the seed event cannot be sent and the listener is removed again
+    EVENTLOOP.send(7u8).expect("failed to send event");
+    let result = EVENTLOOP.bootstrap(4u16, ignore_u16);
+    assert!(matches!(result, Err(BootstrapError::BacklogFull { seed: 4, .. })), "invalid error for full backlog");
+    assert_eq!(EVENTLOOP.listener_count::<u16>(), 0, "listener was not removed");
+
+    // Bootstrap a duplicate listener and recover the seed events
+    EVENTLOOP.listen(ignore_u8).expect("failed to register listener");
+    let Err(error) = EVENTLOOP.bootstrap_many([1u8, 2], ignore_u8) else {
+        panic!("duplicate listener was bootstrapped");
+    };
+    assert!(matches!(error, BootstrapError::Listen { error: ListenError::DuplicateType { .. }, .. }), "invalid error");
+    assert_eq!(error.into_inner(), [1, 2], "invalid recovered seed events");
+}
+
+#[test]
+fn eventloop_enter_with_tick() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// Whether a tick is due
+    static TICK_DUE: AtomicBool = AtomicBool::new(false);
+    /// The amount of ticks
+    static TICKS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Checks and resets the due tick
+    fn should_wake() -> bool {
+        TICK_DUE.swap(false, SeqCst)
+    }
+    /// Counts the tick
+    fn on_tick() {
+        TICKS.fetch_add(1, SeqCst);
+    }
+
+    // Start the event loop and emulate a periodic interrupt
+    thread::spawn(|| EVENTLOOP.enter_with_tick(should_wake, on_tick));
+    for tick in 1..=3 {
+        TICK_DUE.store(true, SeqCst);
+        embedded_eventloop_std::runtime::_runtime_sendevent_ZMWrWpGO();
+
+        // Wait for the tick
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while TICKS.load(SeqCst) < tick {
+            assert!(Instant::now() < deadline, "timeout while waiting for tick");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+    assert_eq!(TICKS.load(SeqCst), 3, "invalid amount of ticks");
+}
+
+#[test]
+fn eventloop_capacity() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<48, 8, 4> = EventLoop::new();
+
+    assert_eq!(EVENTLOOP.stackbox_size(), 48, "invalid stack box size");
+    assert_eq!(EVENTLOOP.backlog_capacity(), 8, "invalid backlog capacity");
+    assert_eq!(EVENTLOOP.listeners_capacity(), 4, "invalid listeners capacity");
+    assert!(EventLoop::<48, 8, 4>::event_fits::<[u8; 48]>(), "event that fits is rejected");
+    assert!(!EventLoop::<48, 8, 4>::event_fits::<[u8; 49]>(), "oversized event is accepted");
+}
+
+#[test]
+fn eventloop_reserve() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 2, 32> = EventLoop::new();
+    /// The sum of the processed events
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    /// Sums the event
+    fn sum(event: u32) -> Option<u32> {
+        SUM.fetch_add(event as usize, SeqCst);
+        None
+    }
+
+    // Reserve both slots and ensure that regular sends fail
+    EVENTLOOP.listen(sum).expect("failed to register listener");
+    let first = EVENTLOOP.reserve::<u32>().expect("failed to reserve slot");
+    let second = EVENTLOOP.reserve::<u32>().expect("failed to reserve slot");
+    assert!(EVENTLOOP.reserve::<u32>().is_none(), "unexpected reservation of full backlog");
+    assert!(EVENTLOOP.is_backlog_full(), "reserved backlog is not full");
+    assert_eq!(EVENTLOOP.send(1u32), Err(1), "unexpected success when sending into reserved slot");
+    assert!(EVENTLOOP.reserve::<[u8; 65]>().is_none(), "unexpected reservation for oversized event");
+
+    // Commit one reservation and release the other one
+    first.commit(7);
+    drop(second);
+    EVENTLOOP.send(3u32).expect("failed to send into released slot");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 10, "invalid sum of processed events");
+}
+
+#[test]
+fn eventloop_listen_where() {
+    /// An input event
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Input {
+        /// A button press
+        Button(u8),
+        /// An encoder rotation
+        Encoder(i8),
+    }
+
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The sum of the button events
+    static BUTTONS: AtomicUsize = AtomicUsize::new(0);
+    /// The amount of events that have been passed on to the fallback listener
+    static FALLBACK: AtomicUsize = AtomicUsize::new(0);
+
+    /// Whether the input is a button press
+    fn is_button(input: &Input) -> bool {
+        matches!(input, Input::Button(_))
+    }
+    /// Sums the button presses
+    fn button(input: Input) -> Option<Input> {
+        let Input::Button(button) = input else {
+            panic!("unexpected input event: {input:?}");
+        };
+        BUTTONS.fetch_add(usize::from(button), SeqCst);
+        None
+    }
+    /// Counts the events that have been passed on
+    fn fallback(input: Input) -> Option<Input> {
+        assert_eq!(input, Input::Encoder(-3), "invalid input event");
+        FALLBACK.fetch_add(1, SeqCst);
+        None
+    }
+
+    // Register the filtered listener first, so that rejected events are passed on to the fallback listener
+    EVENTLOOP.listen_where(is_button, button).expect("failed to register listener");
+    EVENTLOOP.listen(fallback).expect("failed to register listener");
+    EVENTLOOP.send(Input::Button(4)).expect("failed to send event");
+    EVENTLOOP.send(Input::Encoder(-3)).expect("failed to send event");
+    EVENTLOOP.send(Input::Button(5)).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(BUTTONS.load(SeqCst), 9, "invalid sum of button events");
+    assert_eq!(FALLBACK.load(SeqCst), 1, "invalid amount of passed on events");
+}
+
+#[test]
+fn eventloop_try_send() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 3, 32> = EventLoop::new();
+
+    // Fill the backlog and validate the remaining free slots
+    assert_eq!(EVENTLOOP.try_send(1u8), Ok(2), "invalid amount of free slots");
+    let _reservation = EVENTLOOP.reserve::<u8>().expect("failed to reserve slot");
+    assert_eq!(EVENTLOOP.try_send(2u8), Ok(0), "invalid amount of free slots");
+    assert_eq!(EVENTLOOP.try_send(3u8), Err(3), "unexpected success when sending into full backlog");
+}
+
+#[test]
+fn eventloop_listen_macro() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The sum of the processed events
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    /// Sums the event
+    fn sum_u8(event: u8) -> Option<u8> {
+        SUM.fetch_add(usize::from(event), SeqCst);
+        None
+    }
+    /// Sums the event
+    fn sum_u16(event: u16) -> Option<u16> {
+        SUM.fetch_add(usize::from(event), SeqCst);
+        None
+    }
+
+    // Register both listeners at once
+    let (id_u8, id_u16) = embedded_eventloop::listen!(EVENTLOOP, u8 => sum_u8, u16 => sum_u16);
+    id_u8.expect("failed to register listener");
+    id_u16.expect("failed to register listener");
+
+    // Send some events
+    EVENTLOOP.send(4u8).expect("failed to send event");
+    EVENTLOOP.send(300u16).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 304, "invalid sum of processed events");
+}
+
+#[test]
+fn eventloop_send_deferred() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The order of the processed events as decimal digits
+    static ORDER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Appends the event to the order, and triggers itself once deferred and once immediately
+    fn record(event: u8) -> Option<u8> {
+        ORDER.fetch_update(SeqCst, SeqCst, |order| Some(order * 10 + usize::from(event))).expect("failed to record");
+        if event == 1 {
+            EVENTLOOP.send_deferred(2u8).expect("failed to send deferred event");
+            EVENTLOOP.send(3u8).expect("failed to send event");
+        }
+        None
+    }
+
+    // The deferred event is processed after all other pending events
+    EVENTLOOP.listen(record).expect("failed to register listener");
+    EVENTLOOP.send(1u8).expect("failed to send event");
+    EVENTLOOP.send(4u8).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(ORDER.load(SeqCst), 1432, "invalid event order");
+    assert_eq!(EVENTLOOP.backlog_len(), 0, "invalid backlog length");
+}
+
+#[test]
+fn eventloop_next_event() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// Whether the awaiting task has been woken
+    static WOKEN: AtomicBool = AtomicBool::new(false);
+
+    /// A waker that records the wakeup
+    struct FlagWaker;
+    impl Wake for FlagWaker {
+        fn wake(self: Arc<Self>) {
+            WOKEN.store(true, SeqCst);
+        }
+    }
+
+    // Create the future and poll it before the event is sent
+    let waker = Waker::from(Arc::new(FlagWaker));
+    let mut context = Context::from_waker(&waker);
+    let mut next_event = pin!(EVENTLOOP.next_event::<u32>().expect("failed to await event"));
+    assert_eq!(next_event.as_mut().poll(&mut context), Poll::Pending, "unexpected event");
+
+    // Dispatch the event and complete the future
+    EVENTLOOP.send(7u32).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert!(WOKEN.load(SeqCst), "task was not woken");
+    assert_eq!(next_event.as_mut().poll(&mut context), Poll::Ready(Some(7)), "invalid awaited event");
+    assert_eq!(EVENTLOOP.listener_count::<u32>(), 0, "listener was not removed");
+
+    // Drop a pending future
+    let next_event = EVENTLOOP.next_event::<u32>().expect("failed to await event");
+    assert_eq!(EVENTLOOP.listener_count::<u32>(), 1, "listener was not registered");
+    drop(next_event);
+    assert_eq!(EVENTLOOP.listener_count::<u32>(), 0, "listener was not removed");
+}
+
+#[test]
+fn eventloop_next_event_removed() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+
+    // Create the future and poll it before the listener is removed
+    let mut context = Context::from_waker(Waker::noop());
+    let mut next_event = pin!(EVENTLOOP.next_event::<u32>().expect("failed to await event"));
+    assert_eq!(next_event.as_mut().poll(&mut context), Poll::Pending, "unexpected event");
+
+    // Remove the listener while the future is pending, and ensure that the future resolves without an event
+    assert_eq!(EVENTLOOP.clear_listeners(), 1, "invalid amount of removed listeners");
+    assert_eq!(next_event.as_mut().poll(&mut context), Poll::Ready(None), "invalid result for removed listener");
+
+    // Ensure that events are not received by the removed listener
+    EVENTLOOP.send(7u32).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(next_event.as_mut().poll(&mut context), Poll::Ready(None), "removed listener received event");
+}
+
+#[test]
+fn eventloop_overflow_policy() {
+    /// The event loop that drops the oldest event
+    static DROP_OLDEST: EventLoop<64, 2> = EventLoop::with_policy(OverflowPolicy::DropOldest);
+    /// The event loop that panics
+    static PANIC: EventLoop<64, 1> = EventLoop::with_policy(OverflowPolicy::Panic);
+    /// The sum of the processed events
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    /// Sums the event
+    fn sum(event: u32) -> Option<u32> {
+        SUM.fetch_add(event as usize, SeqCst);
+        None
+    }
+
+    // Drop the oldest event
+    DROP_OLDEST.listen(sum).expect("failed to register listener");
+    for event in [1u32, 10, 100] {
+        DROP_OLDEST.send(event).expect("failed to send event");
+    }
+    assert_eq!(DROP_OLDEST.try_send(1000u32), Ok(0), "invalid amount of free slots");
+    DROP_OLDEST.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 1100, "invalid sum of processed events");
+
+    // Panic if the backlog is full
+    PANIC.send(1u32).expect("failed to send event");
+    let result = panic::catch_unwind(|| PANIC.send(2u32));
+    assert!(result.is_err(), "event loop did not panic on full backlog");
+    assert_eq!(PANIC.backlog_len(), 1, "invalid backlog length");
+}
+
+#[test]
+fn eventloop_poll_if() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The amount of processed events
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the event
+    fn count(_event: u8) -> Option<u8> {
+        COUNT.fetch_add(1, SeqCst);
+        None
+    }
+
+    // Defer the event until it is ready
+    EVENTLOOP.listen(count).expect("failed to register listener");
+    EVENTLOOP.send(7u8).expect("failed to send event");
+    assert!(!EVENTLOOP.poll_if(|_| false), "unexpected processed event");
+    assert_eq!(EVENTLOOP.backlog_len(), 1, "deferred event was dropped");
+    assert!(EVENTLOOP.poll_if(|event| event.as_ref::<u8>() == Some(&7)), "failed to process event");
+    assert_eq!(COUNT.load(SeqCst), 1, "invalid amount of processed events");
+}
+
+#[test]
+fn eventloop_listen_after() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The order of the invoked callbacks as decimal digits
+    static ORDER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Appends the given digit to the order
+    fn record(digit: usize) {
+        ORDER.fetch_update(SeqCst, SeqCst, |order| Some(order * 10 + digit)).expect("failed to record");
+    }
+    /// Consumes the event
+    fn consume(event: u32) -> Option<u32> {
+        record(event as usize);
+        None
+    }
+    /// Inspects the event after the listener chain
+    fn after_a(event: &u32) {
+        record(*event as usize + 1);
+    }
+    /// Inspects the event after the listener chain
+    fn after_b(event: &u32) {
+        record(*event as usize + 2);
+    }
+
+    // The late observers see the event even though it has been consumed
+    EVENTLOOP.listen_after(after_a).expect("failed to register late observer");
+    EVENTLOOP.listen(consume).expect("failed to register listener");
+    EVENTLOOP.listen_after(after_b).expect("failed to register late observer");
+    EVENTLOOP.send(1u32).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(ORDER.load(SeqCst), 123, "invalid callback order");
+}
+
+#[test]
+fn eventloop_unlisten_after() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new_unique();
+    /// The amount of observed events
+    static OBSERVED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the event
+    fn observe(_event: &u32) {
+        OBSERVED.fetch_add(1, SeqCst);
+    }
+    /// Consumes the event
+    fn consume(_event: u32) -> Option<u32> {
+        None
+    }
+
+    // Late observers count as listeners of their type for unique event loops
+    let id = EVENTLOOP.listen_after(observe).expect("failed to register late observer");
+    let result = EVENTLOOP.listen_after(observe);
+    assert!(matches!(result, Err(ListenError::DuplicateType { .. })), "duplicate late observer was accepted");
+    let result = EVENTLOOP.listen(consume);
+    assert!(matches!(result, Err(ListenError::DuplicateType { .. })), "duplicate listener was accepted");
+
+    // Remove the late observer
+    EVENTLOOP.send(7u32).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert!(EVENTLOOP.unlisten(id), "failed to remove late observer");
+    assert!(!EVENTLOOP.unlisten(id), "late observer was removed twice");
+    EVENTLOOP.send(7u32).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(OBSERVED.load(SeqCst), 1, "removed late observer was invoked");
+}
+
+#[test]
+fn eventloop_clear() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 32, 32, 2> = EventLoop::new();
+    /// The amount of processed events
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the event
+    fn count(_event: u8) -> Option<u8> {
+        COUNT.fetch_add(1, SeqCst);
+        None
+    }
+    /// Counts the event
+    fn observe(_event: &u8) {
+        COUNT.fetch_add(1, SeqCst);
+    }
+
+    // Clear the pending events of all priorities
+    EVENTLOOP.listen(count).expect("failed to register listener");
+    EVENTLOOP.observe(observe).expect("failed to register observer");
+    EVENTLOOP.listen_after(observe).expect("failed to register late observer");
+    EVENTLOOP.send(1u8).expect("failed to send event");
+    EVENTLOOP.send_prio(1, 2u8).expect("failed to send event");
+    EVENTLOOP.send_deferred(3u8).expect("failed to send event");
+    assert_eq!(EVENTLOOP.clear_backlog(), 3, "invalid amount of removed events");
+    assert_eq!(EVENTLOOP.backlog_len(), 0, "invalid backlog length");
+
+    // Clear the listeners
+    assert_eq!(EVENTLOOP.clear_listeners(), 2, "invalid amount of removed listeners");
+    assert_eq!(EVENTLOOP.listeners_len(), 0, "invalid amount of listeners");
+    EVENTLOOP.send(4u8).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(COUNT.load(SeqCst), 0, "cleared listener was invoked");
+}
+
+#[test]
+fn eventloop_drain_all() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 32, 32, 2> = EventLoop::new();
+
+    // Drain the events in processing order
+    EVENTLOOP.send_deferred(3u8).expect("failed to send event");
+    EVENTLOOP.send(2u8).expect("failed to send event");
+    EVENTLOOP.send_prio(1, 1u8).expect("failed to send event");
+    let mut drained = Vec::new();
+    EVENTLOOP.drain_all(|event_box| drained.push(event_box.into_inner::<u8>().expect("invalid event type")));
+    assert_eq!(drained, [1, 2, 3], "invalid drained events");
+    assert_eq!(EVENTLOOP.backlog_len(), 0, "invalid backlog length");
+}
+
+#[test]
+fn eventloop_process_one_of() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+
+    // Wait for an event that is sent from another thread
+    EVENTLOOP.send(1u8).expect("failed to send event");
+    let sender = thread::spawn(|| {
+        thread::sleep(Duration::from_millis(50));
+        EVENTLOOP.send(2u8).expect("failed to send event");
+        EVENTLOOP.send(7u32).expect("failed to send event");
+    });
+    assert_eq!(EVENTLOOP.process_one_of::<u32>(), Some(7), "invalid event");
+    sender.join().expect("sender thread panicked");
+
+    // Ensure the other events have been kept in order
+    let (pending, _) = EVENTLOOP.drain_into::<u8, 4>();
+    assert_eq!(pending.iter().copied().collect::<Vec<_>>(), [1, 2], "invalid pending events");
+    assert_eq!(EVENTLOOP.process_one_of::<[u8; 128]>(), None, "oversized event type was accepted");
+}
+
+#[test]
+fn eventloop_error_display() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<8> = EventLoop::new();
+
+    // Ensure the errors can be used as `dyn Error`
+    let error: std::boxed::Box<dyn std::error::Error> = SendError::BacklogFull(7u8).into();
+    assert_eq!(error.to_string(), "backlog is full", "invalid error message");
+    let state = [0u8; 16];
+    let Err(error) = EVENTLOOP.listen_fn(move |_: u8| -> Option<u8> { Some(state[0]) }) else {
+        panic!("too large listener was accepted");
+    };
+    let error: std::boxed::Box<dyn std::error::Error> = error.map(|_| ()).into();
+    assert_eq!(error.to_string(), "callback does not fit into stackbox of 8 bytes", "invalid error message");
+}
+
+#[test]
+fn eventloop_dispatch_budget() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new().with_dispatch_budget(2);
+    /// The amount of processed events at each call of the idle hook
+    static IDLE: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+    /// The amount of processed events
+    static PROCESSED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Records the amount of processed events
+    fn idle() {
+        IDLE.lock().expect("failed to lock idle calls").push(PROCESSED.load(SeqCst));
+    }
+    /// Counts the event
+    fn count(_: i64) -> Option<i64> {
+        PROCESSED.fetch_add(1, SeqCst);
+        None
+    }
+
+    // Send some events and start the event loop
+    EVENTLOOP.listen(count).expect("failed to register listener");
+    for event in 0..5i64 {
+        EVENTLOOP.send(event).expect("failed to send event");
+    }
+    thread::spawn(|| EVENTLOOP.enter_with_idle(idle));
+
+    // Wait until the backlog has been processed and ensure the hook has been called after each budget
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while IDLE.lock().expect("failed to lock idle calls").len() < 3 {
+        assert!(Instant::now() < deadline, "timeout while waiting for idle hook");
+        thread::sleep(Duration::from_millis(1));
+    }
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(*IDLE.lock().expect("failed to lock idle calls"), [2, 4, 5], "invalid idle hook calls");
+}
+
+#[test]
+fn eventloop_state_size() {
+    /// The event loop with large events but small listener slots
+    static EVENTLOOP: EventLoop<128, 4, 4, 1, LinkedRuntime, 16> = EventLoop::new();
+    /// The sum of the processed events
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    // Stateful listeners are limited by the listener state size
+    let large = [1u8; 32];
+    let Err(ListenError::TooLarge { available: 16, .. }) =
+        EVENTLOOP.listen_fn(move |event: [u8; 100]| -> Option<[u8; 100]> { Some(event).filter(|_| large[0] == 0) })
+    else {
+        panic!("too large listener was accepted");
+    };
+    let factor = 2usize;
+    let listener = move |event: [u8; 100]| {
+        SUM.fetch_add(event.iter().map(|byte| *byte as usize * factor).sum(), SeqCst);
+        None
+    };
+    assert!(EVENTLOOP.listen_fn(listener).is_ok(), "failed to register stateful listener");
+
+    // Events are limited by the stackbox size
+    EVENTLOOP.send([1u8; 100]).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 200, "invalid sum of processed events");
+    assert_eq!((EVENTLOOP.stackbox_size(), EVENTLOOP.state_size()), (128, 16), "invalid box sizes");
+}
+
+#[test]
+#[cfg(feature = "heapless")]
+fn eventloop_send_vec() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The sum of the received bytes
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    /// Sums the received bytes
+    fn sum(data: heapless::Vec<u8, 16>) -> Option<heapless::Vec<u8, 16>> {
+        SUM.fetch_add(data.iter().map(|byte| *byte as usize).sum(), SeqCst);
+        None
+    }
+
+    // Send a vector payload
+    EVENTLOOP.listen(sum).expect("failed to register listener");
+    let mut data = heapless::Vec::<u8, 16>::new();
+    for byte in 1..=4 {
+        data.push(byte).expect("failed to push byte");
+    }
+    EVENTLOOP.send_vec(data).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 10, "invalid sum of received bytes");
+}
+
+#[test]
+fn eventloop_snapshot_listeners() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The amount of invocations of plain function listeners and observers
+    static PLAIN: AtomicUsize = AtomicUsize::new(0);
+    /// The amount of invocations of stateful listeners
+    static STATEFUL: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the event and passes it on
+    fn listen(event: u8) -> Option<u8> {
+        PLAIN.fetch_add(1, SeqCst);
+        Some(event)
+    }
+    /// Counts the event
+    fn observe(_event: &u8) {
+        PLAIN.fetch_add(1, SeqCst);
+    }
+
+    // Register some listeners and take a snapshot
+    let listener_id = EVENTLOOP.listen(listen).expect("failed to register listener");
+    EVENTLOOP.observe(observe).expect("failed to register observer");
+    EVENTLOOP.listen_after(observe).expect("failed to register late observer");
+    let stateful = move |event: u8| {
+        STATEFUL.fetch_add(1, SeqCst);
+        Some(event)
+    };
+    assert!(EVENTLOOP.listen_fn(stateful).is_ok(), "failed to register stateful listener");
+    let snapshot = EVENTLOOP.snapshot_listeners();
+    assert_eq!(snapshot.len(), 3, "invalid amount of captured listeners");
+    assert!(!snapshot.is_empty(), "snapshot is empty");
+
+    // Remove all listeners and restore the snapshot twice
+    for _ in 0..2 {
+        EVENTLOOP.clear_listeners();
+        assert_eq!(EVENTLOOP.restore_listeners(snapshot.clone()), 3, "invalid amount of restored listeners");
+        assert_eq!(EVENTLOOP.listeners_len(), 2, "invalid amount of listeners");
+    }
+
+    // Ensure the restored listeners are invoked and keep their handles
+    EVENTLOOP.send(7u8).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(PLAIN.load(SeqCst), 3, "invalid amount of invocations");
+    assert_eq!(STATEFUL.load(SeqCst), 0, "stateful listener was restored");
+    assert!(EVENTLOOP.unlisten(listener_id), "restored listener has a different handle");
+}
+
+#[test]
+fn eventloop_snapshot_late_observers() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+
+    /// Ignores the event
+    fn observe(_event: &u8) {}
+
+    // Ensure that a snapshot with only late observers is consistent
+    assert!(EVENTLOOP.snapshot_listeners().is_empty(), "snapshot of empty event loop is not empty");
+    EVENTLOOP.listen_after(observe).expect("failed to register late observer");
+    let snapshot = EVENTLOOP.snapshot_listeners();
+    assert_eq!(snapshot.len(), 1, "invalid amount of captured late observers");
+    assert!(!snapshot.is_empty(), "snapshot is empty");
+}
+
+#[test]
+fn eventloop_send_update() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 2> = EventLoop::new();
+    /// The received knob positions
+    static RECEIVED: Mutex<Vec<u16>> = Mutex::new(Vec::new());
+
+    /// Records the knob position
+    fn record(position: u16) -> Option<u16> {
+        RECEIVED.lock().expect("failed to lock received positions").push(position);
+        None
+    }
+
+    // Send a new event and update it in place afterwards
+    EVENTLOOP.listen(record).expect("failed to register listener");
+    EVENTLOOP.send(7u8).expect("failed to send event");
+    for position in 1..=3u16 {
+        EVENTLOOP.send_update(|| 0u16, |pending| *pending += position).expect("failed to send event");
+    }
+    assert_eq!(EVENTLOOP.backlog_len(), 2, "invalid backlog length");
+
+    // A new event is rejected if the backlog is full
+    EVENTLOOP.run_until_idle();
+    EVENTLOOP.send(7u8).expect("failed to send event");
+    EVENTLOOP.send(8u8).expect("failed to send event");
+    assert_eq!(EVENTLOOP.send_update(|| 4u16, |_| unreachable!()), Err(4), "event was accepted by full backlog");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(*RECEIVED.lock().expect("failed to lock received positions"), [5], "invalid received positions");
+}
+
+#[test]
+fn eventloop_local() {
+    /// The sum of the received events
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    /// Sums the received event
+    fn sum(event: Rc<usize>) -> Option<Rc<usize>> {
+        SUM.fetch_add(*event, SeqCst);
+        None
+    }
+
+    // A local event loop accepts events that are not `Send`
+    let eventloop: LocalEventLoop = LocalEventLoop::new();
+    eventloop.listen(sum).expect("failed to register listener");
+    let event = Rc::new(7usize);
+    eventloop.send(Rc::clone(&event)).expect("failed to send event");
+    eventloop.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 7, "invalid sum of received events");
+    assert_eq!(Rc::strong_count(&event), 1, "invalid reference count");
+}
+
+#[test]
+fn eventloop_listen_front() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 32, 4> = EventLoop::new();
+    /// The invoked listeners in dispatch order
+    static INVOKED: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+    /// Ignores events of another type
+    fn other(_: u16) -> Option<u16> {
+        None
+    }
+    /// Swallows odd events
+    fn filter(event: u8) -> Option<u8> {
+        INVOKED.lock().expect("failed to lock invoked listeners").push("filter");
+        event.is_multiple_of(2).then_some(event)
+    }
+    /// Passes the event on
+    fn first(event: u8) -> Option<u8> {
+        INVOKED.lock().expect("failed to lock invoked listeners").push("first");
+        Some(event)
+    }
+    /// Consumes the event
+    fn second(_: u8) -> Option<u8> {
+        INVOKED.lock().expect("failed to lock invoked listeners").push("second");
+        None
+    }
+
+    // Register the listeners, then install the filter in front of the existing chain
+    EVENTLOOP.listen(other).expect("failed to register listener");
+    let first_id = EVENTLOOP.listen(first).expect("failed to register listener");
+    EVENTLOOP.listen(second).expect("failed to register listener");
+    EVENTLOOP.listen_front(filter).expect("failed to register listener");
+    assert!(EVENTLOOP.listen_front(filter).is_err(), "unexpected success when listener slots are full");
+
+    // Validate that the filter is invoked first and ends the chain for odd events
+    EVENTLOOP.send(2u8).expect("failed to send event");
+    EVENTLOOP.send(3u8).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    let invoked = INVOKED.lock().expect("failed to lock invoked listeners").split_off(0);
+    assert_eq!(invoked, ["filter", "first", "second", "filter"], "invalid dispatch order");
+
+    // Validate that the handle of a moved listener is still valid
+    assert!(EVENTLOOP.unlisten(first_id), "failed to remove moved listener");
+    EVENTLOOP.send(4u8).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    let invoked = INVOKED.lock().expect("failed to lock invoked listeners").split_off(0);
+    assert_eq!(invoked, ["filter", "second"], "invalid dispatch order after removal");
+}
@@ -0,0 +1,44 @@
+//! A mock runtime
+
+use embedded_eventloop::EventLoop;
+use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+embedded_eventloop::mock_runtime!();
+
+#[test]
+fn mock_runtime_send_event() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The amount of raised events
+    static SENT: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the raised event
+    fn count() {
+        SENT.fetch_add(1, SeqCst);
+    }
+
+    // Send some events and count the raised events
+    mock_runtime::set_send_event(count);
+    for event in 0..3u8 {
+        EVENTLOOP.send(event).expect("failed to send event");
+    }
+    assert_eq!(SENT.load(SeqCst), 3, "invalid amount of raised events");
+}
+
+#[test]
+fn mock_runtime_wait_for_event() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The amount of waits
+    static WAITS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the wait and returns immediately
+    fn wait() {
+        WAITS.fetch_add(1, SeqCst);
+    }
+
+    // Run the event loop until it has waited a few times
+    mock_runtime::set_wait_for_event(wait);
+    EVENTLOOP.enter_until(|| WAITS.load(SeqCst) >= 3);
+    assert_eq!(WAITS.load(SeqCst), 3, "invalid amount of waits");
+}
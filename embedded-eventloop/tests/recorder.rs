@@ -0,0 +1,145 @@
+//! An event recorder
+
+use embedded_eventloop::{recorder::Recorder, EventLoop, LocalEventLoop};
+use embedded_eventloop_std as _;
+use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+#[test]
+fn recorder_replay() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The recorder
+    static RECORDER: Recorder<64, 2> = Recorder::new();
+    /// The sum of the processed events
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    /// Sums the event
+    fn sum(event: u32) -> Option<u32> {
+        SUM.fetch_add(event as usize, SeqCst);
+        None
+    }
+    /// Ignores the event
+    fn ignore(_: i8) -> Option<i8> {
+        None
+    }
+
+    // Record the last two `u32` events only
+    assert!(RECORDER.allow::<u32>(), "failed to allow event type");
+    EVENTLOOP.set_recorder(&RECORDER);
+    EVENTLOOP.listen(sum).expect("failed to register listener");
+    EVENTLOOP.listen(ignore).expect("failed to register listener");
+    for event in [1u32, 10, 100] {
+        EVENTLOOP.send(event).expect("failed to send event");
+        EVENTLOOP.send(-1i8).expect("failed to send event");
+    }
+    EVENTLOOP.run_until_idle();
+    assert_eq!(RECORDER.len(), 2, "invalid amount of recorded events");
+
+    // Replay the recorded events
+    assert_eq!(RECORDER.replay(&EVENTLOOP), 2, "invalid amount of replayed events");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 221, "invalid sum of processed events");
+}
+
+#[test]
+fn recorder_replay_padded() {
+    /// An event with padding bytes
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(C)]
+    struct Padded {
+        /// The event kind
+        kind: u8,
+        /// The event value
+        value: u32,
+    }
+
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The recorder
+    static RECORDER: Recorder = Recorder::new();
+    /// The sum of the processed events
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    /// Sums the event
+    fn sum(event: Padded) -> Option<Padded> {
+        SUM.fetch_add(event.kind as usize + event.value as usize, SeqCst);
+        None
+    }
+
+    // Record and replay an event with padding bytes
+    assert!(RECORDER.allow::<Padded>(), "failed to allow event type");
+    EVENTLOOP.set_recorder(&RECORDER);
+    EVENTLOOP.listen(sum).expect("failed to register listener");
+    EVENTLOOP.send(Padded { kind: 7, value: 40 }).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(RECORDER.replay(&EVENTLOOP), 1, "invalid amount of replayed events");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 94, "invalid sum of processed events");
+}
+
+#[test]
+fn recorder_replay_broadcast() {
+    /// The event loop
+    static EVENTLOOP: EventLoop = EventLoop::new();
+    /// The recorder
+    static RECORDER: Recorder = Recorder::new();
+    /// The amount of events processed by the first listener
+    static FIRST: AtomicUsize = AtomicUsize::new(0);
+    /// The amount of events processed by the second listener
+    static SECOND: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the event and consumes it
+    fn first(_: u32) -> Option<u32> {
+        FIRST.fetch_add(1, SeqCst);
+        None
+    }
+    /// Counts the event and consumes it
+    fn second(_: u32) -> Option<u32> {
+        SECOND.fetch_add(1, SeqCst);
+        None
+    }
+
+    // Record a broadcast event and a regular event
+    assert!(RECORDER.allow::<u32>(), "failed to allow event type");
+    EVENTLOOP.set_recorder(&RECORDER);
+    EVENTLOOP.listen(first).expect("failed to register listener");
+    EVENTLOOP.listen(second).expect("failed to register listener");
+    EVENTLOOP.send_broadcast(7u32).expect("failed to send event");
+    EVENTLOOP.send(4u32).expect("failed to send event");
+    EVENTLOOP.run_until_idle();
+    assert_eq!((FIRST.load(SeqCst), SECOND.load(SeqCst)), (2, 1), "invalid amount of processed events");
+
+    // Replay the events; the broadcast event must reach both listeners again
+    assert_eq!(RECORDER.replay(&EVENTLOOP), 2, "invalid amount of replayed events");
+    EVENTLOOP.run_until_idle();
+    assert_eq!((FIRST.load(SeqCst), SECOND.load(SeqCst)), (4, 2), "invalid amount of processed events");
+}
+
+#[test]
+fn recorder_replay_local() {
+    /// The recorder
+    static RECORDER: Recorder = Recorder::new();
+    /// The sum of the processed events
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    /// Sums the event
+    fn sum(event: u32) -> Option<u32> {
+        SUM.fetch_add(event as usize, SeqCst);
+        None
+    }
+
+    // Record the events of a thread-local event loop
+    let eventloop: LocalEventLoop = LocalEventLoop::new();
+    assert!(RECORDER.allow::<u32>(), "failed to allow event type");
+    eventloop.set_recorder(&RECORDER);
+    eventloop.listen(sum).expect("failed to register listener");
+    for event in [1u32, 10, 100] {
+        eventloop.send(event).expect("failed to send event");
+    }
+    eventloop.run_until_idle();
+
+    // Replay the recorded events into the thread-local event loop
+    assert_eq!(RECORDER.replay(&eventloop), 3, "invalid amount of replayed events");
+    eventloop.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 222, "invalid sum of processed events");
+}
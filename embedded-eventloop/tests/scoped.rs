@@ -0,0 +1,61 @@
+//! The scoped event loop
+
+use embedded_eventloop::ScopedEventLoop;
+use embedded_eventloop_std::testing::{RuntimeCall, TestRuntime};
+
+#[test]
+fn scopedeventloop_borrowed() {
+    /// An event that borrows from the stack
+    #[derive(Debug)]
+    enum Event<'a> {
+        /// A chunk of data
+        Data(&'a [u8]),
+        /// All data has been processed
+        Done,
+    }
+
+    // Send borrowed chunks of a stack buffer
+    let buf = [1u8, 2, 3, 4, 5];
+    let (mut sum, mut done) = (0, false);
+    let sent = ScopedEventLoop::<Event, 4>::scope(
+        |eventloop, event| match event {
+            Event::Data(data) => {
+                sum += data.iter().map(|byte| u32::from(*byte)).sum::<u32>();
+                if eventloop.backlog_len() == 0 {
+                    eventloop.send(Event::Done).expect("failed to send event");
+                }
+            }
+            Event::Done => done = true,
+        },
+        |eventloop| {
+            let chunks = buf.chunks(2);
+            let count = chunks.len();
+            chunks.for_each(|chunk| eventloop.send(Event::Data(chunk)).expect("failed to send event"));
+            count
+        },
+    );
+
+    // Validate that all events have been processed before the scope returned
+    assert_eq!(sent, 3, "invalid amount of sent events");
+    assert_eq!(sum, 15, "invalid sum of processed data");
+    assert!(done, "follow-up event was not processed");
+}
+
+#[test]
+fn scopedeventloop_runtime() {
+    // Use a custom runtime instead of the linked runtime
+    TestRuntime::take_calls();
+    let mut received = Vec::new();
+    ScopedEventLoop::<u8, 4, TestRuntime>::scope(
+        |_, event| received.push(event),
+        |eventloop| {
+            eventloop.send(7).expect("failed to send event");
+            eventloop.send(4).expect("failed to send event");
+        },
+    );
+
+    // Validate that the events have been processed within the custom runtime's critical section
+    assert_eq!(received, [7, 4], "invalid processed events");
+    let calls = TestRuntime::take_calls();
+    assert!(calls.contains(&RuntimeCall::CriticalSection), "custom runtime was not used");
+}
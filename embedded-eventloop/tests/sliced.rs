@@ -0,0 +1,91 @@
+//! The slice-backed event loop
+
+use embedded_eventloop::{boxes::Box, runtime::LinkedRuntime, EventListener, ListenError, Local, SliceEventLoop};
+use embedded_eventloop_std as _;
+use std::{
+    cell::Cell,
+    iter,
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering::SeqCst},
+};
+
+#[test]
+fn sliceeventloop() {
+    /// The sum of all processed events
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    // Allocate the storage with a runtime capacity
+    let capacity = 3;
+    let backlog: Vec<Option<Box<64>>> = iter::repeat_with(|| None).take(capacity).collect();
+    let listeners: Vec<Option<EventListener<64>>> = iter::repeat_with(|| None).take(2).collect();
+    let eventloop: SliceEventLoop =
+        SliceEventLoop::new(std::boxed::Box::leak(backlog.into()), std::boxed::Box::leak(listeners.into()));
+    assert_eq!(eventloop.backlog_capacity(), capacity, "invalid backlog capacity");
+    assert_eq!(eventloop.listeners_capacity(), 2, "invalid listeners capacity");
+
+    // Register a chain of two listeners and ensure that the listener slots are exhausted
+    let first = eventloop.listen(|value: usize| Some(value * 2)).expect("failed to register listener");
+    let second = eventloop.listen(|value: usize| {
+        SUM.fetch_add(value, SeqCst);
+        None
+    });
+    second.expect("failed to register listener");
+    let full = eventloop.listen(|value: usize| Some(value));
+    assert!(
+        matches!(full, Err(ListenError::Full { capacity: 2, .. })),
+        "unexpected success when listener slots are full"
+    );
+
+    // Fill the backlog and ensure that it is bounded
+    for value in 1..=3usize {
+        eventloop.send(value).expect("failed to send event");
+    }
+    assert_eq!(eventloop.send(4usize), Err(4), "unexpected success when backlog is full");
+    eventloop.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 12, "invalid sum of processed events");
+
+    // Remove the first listener and ensure that its slot is released
+    assert!(eventloop.unlisten(first), "failed to remove listener");
+    assert!(!eventloop.unlisten(first), "unexpected success when removing listener twice");
+    assert_eq!(eventloop.listeners_len(), 1, "invalid amount of listeners");
+    eventloop.send(5usize).expect("failed to send event");
+    eventloop.run_until_idle();
+    assert_eq!(SUM.load(SeqCst), 17, "invalid sum of processed events");
+}
+
+#[test]
+fn sliceeventloop_listen_too_large() {
+    // Allocate the storage
+    let backlog: Vec<Option<Box<64>>> = iter::repeat_with(|| None).take(1).collect();
+    let listeners: Vec<Option<EventListener<64>>> = iter::repeat_with(|| None).take(1).collect();
+    let eventloop: SliceEventLoop =
+        SliceEventLoop::new(std::boxed::Box::leak(backlog.into()), std::boxed::Box::leak(listeners.into()));
+
+    // Ensure that a closure that does not fit into the stack box is rejected
+    let buf = [0u8; 128];
+    let result = eventloop.listen_fn(move |value: usize| Some(value + usize::from(buf[0])));
+    assert!(matches!(result, Err(ListenError::TooLarge { available: 64, .. })), "unexpected success for large closure");
+    assert_eq!(eventloop.listeners_len(), 0, "rejected listener was registered");
+}
+
+#[test]
+fn sliceeventloop_local() {
+    // Allocate the storage
+    let backlog: Vec<Option<Box<64>>> = iter::repeat_with(|| None).take(2).collect();
+    let listeners: Vec<Option<EventListener<64>>> = iter::repeat_with(|| None).take(1).collect();
+    let eventloop: SliceEventLoop<64, LinkedRuntime, Local> =
+        SliceEventLoop::new(std::boxed::Box::leak(backlog.into()), std::boxed::Box::leak(listeners.into()));
+
+    // Ensure that events and listener states need not be `Send`
+    let sum = Rc::new(Cell::new(0));
+    let listener_sum = Rc::clone(&sum);
+    let listener = move |value: Rc<usize>| {
+        listener_sum.set(listener_sum.get() + *value);
+        None
+    };
+    assert!(eventloop.listen_fn(listener).is_ok(), "failed to register stateful listener");
+    eventloop.send(Rc::new(4usize)).expect("failed to send event");
+    eventloop.send(Rc::new(5usize)).expect("failed to send event");
+    eventloop.run_until_idle();
+    assert_eq!(sum.get(), 9, "invalid sum of processed events");
+}
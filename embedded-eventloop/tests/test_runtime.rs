@@ -0,0 +1,44 @@
+//! The recording test runtime
+
+use embedded_eventloop::EventLoop;
+use embedded_eventloop_std::testing::{RuntimeCall, TestRuntime};
+
+#[test]
+fn test_runtime_send() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 32, 32, 1, TestRuntime> = EventLoop::new();
+
+    // The event must be enqueued within a critical section before the hardware event is raised
+    TestRuntime::take_calls();
+    EVENTLOOP.send(7u8).expect("failed to send event");
+    let calls = TestRuntime::take_calls();
+    let (last, enqueue) = calls.split_last().expect("missing runtime calls");
+    assert_eq!(*last, RuntimeCall::SendEvent, "hardware event was not raised last");
+    assert!(!enqueue.is_empty(), "event was not enqueued within a critical section");
+    assert!(enqueue.iter().all(|call| *call == RuntimeCall::CriticalSection), "invalid calls before raising event");
+}
+
+#[test]
+fn test_runtime_wait() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 32, 32, 1, TestRuntime> = EventLoop::new();
+
+    /// Stops the event loop after it has waited twice
+    fn should_stop() -> bool {
+        TestRuntime::calls().iter().filter(|call| **call == RuntimeCall::WaitForEvent).count() >= 2
+    }
+
+    // Process an event and wait until the loop has gone to sleep twice
+    EVENTLOOP.send(7u8).expect("failed to send event");
+    TestRuntime::take_calls();
+    EVENTLOOP.enter_until(should_stop);
+
+    // Each wait must immediately follow the check that the backlog is empty, so that no event can get lost
+    let calls = TestRuntime::take_calls();
+    let waits: Vec<_> = (0..calls.len()).filter(|index| calls[*index] == RuntimeCall::WaitForEvent).collect();
+    assert_eq!(waits.len(), 2, "invalid amount of waits");
+    for index in waits {
+        assert_eq!(calls.get(index.wrapping_sub(1)), Some(&RuntimeCall::CriticalSection), "wait without prior check");
+    }
+    assert!(!calls.contains(&RuntimeCall::SendEvent), "unexpected hardware event");
+}
@@ -0,0 +1,93 @@
+//! A threadsafe cell type
+
+use embedded_eventloop::threadsafe::{self, ThreadSafeCell};
+use embedded_eventloop_std as _;
+
+#[test]
+fn threadsafecell_try_scope() {
+    // Access the value
+    let cell = ThreadSafeCell::new(7u8);
+    assert_eq!(cell.try_scope(|value| *value), Some(7), "failed to access value");
+
+    // Try to access the value again from within a scope
+    let nested = cell.scope(|value| {
+        *value = 4;
+        cell.try_scope(|value| *value)
+    });
+    assert_eq!(nested, None, "unexpected success when accessing borrowed value");
+
+    // Ensure the value is accessible again after the scope
+    assert_eq!(cell.try_scope(|value| *value), Some(4), "failed to access value after scope");
+}
+
+#[test]
+fn threadsafecell_replace() {
+    // Replace and take the value
+    let cell = ThreadSafeCell::new(7u8);
+    assert_eq!(cell.replace(4), 7, "invalid previous value");
+    assert_eq!(cell.take(), 4, "invalid taken value");
+    assert_eq!(cell.scope(|value| *value), 0, "invalid default value");
+}
+
+#[test]
+fn threadsafecell_swap() {
+    // Swap the values of two cells
+    let cell = ThreadSafeCell::new(7u8);
+    let other = ThreadSafeCell::new(4u8);
+    cell.swap(&other);
+    assert_eq!(cell.scope(|value| *value), 4, "invalid swapped value");
+    assert_eq!(other.scope(|value| *value), 7, "invalid swapped value");
+
+    // Swap a cell with itself
+    cell.swap(&cell);
+    assert_eq!(cell.scope(|value| *value), 4, "invalid value after swapping with itself");
+}
+
+#[test]
+fn threadsafecell_scope2() {
+    // Move a value from a queue cell into a state cell
+    let queue = ThreadSafeCell::new(Some(7u8));
+    let state = ThreadSafeCell::new(0u16);
+    let moved = threadsafe::scope2(&queue, &state, |queue, state| {
+        *state = u16::from(queue.take().expect("missing value"));
+        *state
+    });
+    assert_eq!(moved, 7, "invalid moved value");
+    assert_eq!(queue.scope(|value| *value), None, "invalid value after move");
+    assert_eq!(state.scope(|value| *value), 7, "invalid value after move");
+}
+
+#[test]
+fn threadsafecell_get_mut() {
+    // Modify the value via an exclusive reference
+    let mut cell = ThreadSafeCell::new(7u8);
+    *cell.get_mut() = 4;
+    assert_eq!(cell.scope(|value| *value), 4, "invalid modified value");
+}
+
+#[test]
+fn threadsafecell_into_inner() {
+    // Consume the cell
+    let cell = ThreadSafeCell::new(7u8);
+    cell.scope(|value| *value = 4);
+    assert_eq!(cell.into_inner(), 4, "invalid inner value");
+}
+
+#[test]
+#[should_panic(expected = "re-entrant access")]
+fn threadsafecell_scope_reentrant() {
+    // Re-enter a scope on the same cell
+    let cell = ThreadSafeCell::new(7u8);
+    cell.scope(|_| cell.scope(|value| *value));
+}
+
+#[test]
+fn threadsafecell_debug_locked() {
+    // Format the value
+    let cell = ThreadSafeCell::new(7u8);
+    assert_eq!(format!("{cell:?}"), "ThreadSafe { value: 7 }", "invalid debug representation");
+
+    // Format the cell from within a scope
+    let nested = cell.scope(|_| format!("{cell:?}"));
+    assert_eq!(nested, "ThreadSafe { <locked> }", "invalid debug representation of borrowed cell");
+}
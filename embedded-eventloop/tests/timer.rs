@@ -0,0 +1,45 @@
+//! A timer wheel
+
+use embedded_eventloop::{
+    timer::{Timeout, TimerWheel},
+    EventLoop,
+};
+use embedded_eventloop_std as _;
+use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+#[test]
+fn timer_advance() {
+    /// The event loop
+    static EVENTLOOP: EventLoop<64, 1> = EventLoop::new();
+    /// The timer wheel
+    static TIMERS: TimerWheel<2> = TimerWheel::new();
+    /// The order of the fired timers as decimal digits
+    static ORDER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Appends the given digit to the order
+    fn record(digit: usize) {
+        ORDER.fetch_update(SeqCst, SeqCst, |order| Some(order * 10 + digit)).expect("failed to record");
+    }
+    /// Fires the timeout
+    fn fire(timeout: Timeout) -> Option<Timeout> {
+        timeout.fire();
+        None
+    }
+
+    // Schedule the timers
+    EVENTLOOP.listen(fire).expect("failed to register listener");
+    TIMERS.schedule(20, || record(2)).expect("failed to schedule timer");
+    TIMERS.schedule(10, || record(1)).expect("failed to schedule timer");
+    assert!(TIMERS.schedule(30, || record(3)).is_err(), "scheduled too many timers");
+
+    // No timer is due yet
+    assert_eq!(TIMERS.advance(9, &EVENTLOOP), 0, "invalid amount of sent timeouts");
+
+    // Both timers are due, but only one fits into the backlog
+    assert_eq!(TIMERS.advance(25, &EVENTLOOP), 1, "invalid amount of sent timeouts");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(TIMERS.advance(25, &EVENTLOOP), 1, "invalid amount of sent timeouts");
+    EVENTLOOP.run_until_idle();
+    assert_eq!(ORDER.load(SeqCst), 12, "invalid timer order");
+    assert!(TIMERS.is_empty(), "timer wheel is not empty");
+}